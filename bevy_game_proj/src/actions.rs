@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+
+use bevy::{input::InputSystem, prelude::*};
+use game_common::bindings::{apply_deadzone, Binding, GameAction};
+use serde::{Deserialize, Serialize};
+
+// set this to use a bindings file somewhere other than the platform config dir
+pub const BINDINGS_PATH_ENV: &str = "STAR_COLLECTOR_BINDINGS";
+
+pub type InputBindings = game_common::bindings::InputBindings<Action>;
+
+// turns raw keyboard and gamepad state into `Input<Action>` and `MoveAxis` every frame.
+// the bindings file is loaded by game_common's BindingsFilePlugin
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<InputBindings>()
+        .init_resource::<Input<Action>>()
+        .init_resource::<MoveAxis>()
+        .add_systems(PreUpdate, update_actions.after(InputSystem));
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Pause,
+    Confirm,
+    // pauses a run, only closes the game from the main menu or game over screen
+    Quit,
+}
+
+impl GameAction for Action {
+    fn default_bindings() -> BTreeMap<Action, Binding> {
+        BTreeMap::from([
+            (Action::MoveUp, Binding::new(&[KeyCode::Up, KeyCode::W], &[GamepadButtonType::DPadUp])),
+            (Action::MoveDown, Binding::new(&[KeyCode::Down, KeyCode::S], &[GamepadButtonType::DPadDown])),
+            (Action::MoveLeft, Binding::new(&[KeyCode::Left, KeyCode::A], &[GamepadButtonType::DPadLeft])),
+            (Action::MoveRight, Binding::new(&[KeyCode::Right, KeyCode::D], &[GamepadButtonType::DPadRight])),
+            (Action::Pause, Binding::new(&[KeyCode::P], &[GamepadButtonType::Start])),
+            (Action::Confirm, Binding::new(&[KeyCode::Space], &[GamepadButtonType::South])),
+            (Action::Quit, Binding::new(&[KeyCode::Escape], &[GamepadButtonType::Select])),
+        ])
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Pause => "Pause",
+            Action::Confirm => "Confirm",
+            Action::Quit => "Back",
+        }
+    }
+}
+
+// combined movement from every bound key, d-pad and left stick. Keeps the stick's analog
+// magnitude, so its length is anywhere from 0 to 1
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct MoveAxis {
+    pub value: Vec2,
+}
+
+pub fn update_actions(
+    bindings: Res<InputBindings>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut actions: ResMut<Input<Action>>,
+    mut move_axis: ResMut<MoveAxis>,
+) {
+    bindings.update_actions(&keyboard_input, &gamepads, &gamepad_buttons, &mut actions);
+
+    let mut digital = Vec2::ZERO;
+    if actions.pressed(Action::MoveLeft) {
+        digital.x -= 1.0;
+    }
+    if actions.pressed(Action::MoveRight) {
+        digital.x += 1.0;
+    }
+    if actions.pressed(Action::MoveDown) {
+        digital.y -= 1.0;
+    }
+    if actions.pressed(Action::MoveUp) {
+        digital.y += 1.0;
+    }
+    let digital = digital.normalize_or_zero();
+
+    let mut analog = Vec2::ZERO;
+    for gamepad in gamepads.iter() {
+        let stick = Vec2::new(
+            gamepad_axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX)).unwrap_or(0.0),
+            gamepad_axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY)).unwrap_or(0.0),
+        );
+        let stick = apply_deadzone(stick, bindings.stick_deadzone);
+        if stick.length() > analog.length() {
+            analog = stick;
+        }
+    }
+
+    move_axis.value = if analog.length() > digital.length() { analog } else { digital };
+}
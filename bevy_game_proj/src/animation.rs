@@ -0,0 +1,224 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::{
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::components::{Knockback, Player};
+use crate::resources::PlayerInput;
+use crate::ron_asset::{RonAsset, RonAssetPlugin};
+use crate::states::AppState;
+
+pub const ANIMATIONS_PATH: &str = "animations/star_collector.anim.ron";
+pub const MAX_ANIMATION_FPS: f32 = 120.0;
+
+// the sprite sheets are laid out on a grid of equally sized frames
+pub const PLAYER_SHEET: &str = "sprites/player_sheet.png";
+pub const PLAYER_SHEET_GRID: (usize, usize) = (4, 3);
+pub const ENEMY_SHEET: &str = "sprites/enemy_sheet.png";
+// one row per enemy kind, in EnemyKind::ALL order
+pub const ENEMY_SHEET_GRID: (usize, usize) = (4, 4);
+pub const STAR_SHEET: &str = "sprites/star_sheet.png";
+pub const STAR_SHEET_GRID: (usize, usize) = (8, 1);
+
+pub const PLAYER_IDLE: &str = "player_idle";
+pub const PLAYER_MOVING: &str = "player_moving";
+pub const PLAYER_HIT: &str = "player_hit";
+pub const ENEMY_PULSE: &str = "enemy_pulse";
+pub const CHASER_SPIN: &str = "chaser_spin";
+pub const WANDERER_WOBBLE: &str = "wanderer_wobble";
+pub const DASHER_PULSE: &str = "dasher_pulse";
+pub const STAR_SPIN: &str = "star_spin";
+
+// loads the clip file and plays the clips. Like the rest of the visuals it runs on the frame clock,
+// the simulation only picks which clip an Animator should be playing
+pub struct SpriteAnimationPlugin;
+
+impl Plugin for SpriteAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<AnimationLibrary>()
+        .add_plugins(RonAssetPlugin::<AnimationLibrary>::new(ANIMATIONS_PATH))
+        .add_systems(Update, (choose_player_animation, animate_sprites)
+            .chain()
+            .run_if(not(in_state(AppState::Paused))));
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct AnimationClip {
+    // indices into the sprite sheet, played in this order
+    pub frames: Vec<usize>,
+    pub fps: f32,
+    // a clip that doesn't loop holds its last frame
+    #[serde(default = "default_looping")]
+    pub looping: bool,
+}
+
+impl AnimationClip {
+    // the frame `steps` frames on from `frame`, wrapping around or holding the last one.
+    // `frame` may be left over from a longer clip
+    pub fn advance(&self, frame: usize, steps: usize) -> usize {
+        let last = self.frames.len() - 1;
+        let advanced = frame.min(last) + steps;
+        if self.looping { advanced % self.frames.len() } else { advanced.min(last) }
+    }
+}
+
+fn default_looping() -> bool {
+    true
+}
+
+// every clip the game knows about, by name
+#[derive(Resource, Serialize, Deserialize, TypeUuid, TypePath, Clone, PartialEq, Debug, Default)]
+#[uuid = "c41e7a93-5f2d-4b68-8e0a-6d9b3f1c2e57"]
+pub struct AnimationLibrary {
+    pub clips: HashMap<String, AnimationClip>,
+}
+
+impl RonAsset for AnimationLibrary {
+    const NAME: &'static str = "animations";
+    const EXTENSIONS: &'static [&'static str] = &["anim.ron"];
+
+    fn validate(&self) -> Result<(), String> {
+        for (name, clip) in self.clips.iter() {
+            if clip.frames.is_empty() {
+                return Err(format!("clip `{}` has no frames", name));
+            }
+            let fps_in_range = clip.fps > 0.0 && clip.fps <= MAX_ANIMATION_FPS;
+            if !fps_in_range {
+                return Err(format!("clip `{}` runs at {} fps, it must be above 0 and at most {}", name, clip.fps, MAX_ANIMATION_FPS));
+            }
+        }
+        Ok(())
+    }
+}
+
+// builds the atlas for a sheet whose frames are all `tile_size` big
+pub fn sheet_atlas(
+    asset_server: &AssetServer,
+    atlases: &mut Assets<TextureAtlas>,
+    path: &'static str,
+    tile_size: f32,
+    (columns, rows): (usize, usize),
+) -> Handle<TextureAtlas> {
+    atlases.add(TextureAtlas::from_grid(asset_server.load(path), Vec2::splat(tile_size), columns, rows, None, None))
+}
+
+// plays a named clip from the AnimationLibrary on the entity's TextureAtlasSprite
+#[derive(Component)]
+pub struct Animator {
+    pub clip: &'static str,
+    pub frame: usize,
+    pub timer: Timer,
+}
+
+impl Animator {
+    pub fn new(clip: &'static str) -> Animator {
+        Animator {
+            clip,
+            frame: 0,
+            timer: Timer::default(),
+        }
+    }
+
+    // restarts from the first frame, unless that clip is already playing
+    pub fn play(&mut self, clip: &'static str) {
+        if self.clip != clip {
+            self.clip = clip;
+            self.frame = 0;
+            self.timer.reset();
+        }
+    }
+}
+
+pub fn choose_player_animation(
+    player_input: Res<PlayerInput>,
+    mut player_query: Query<(&mut Animator, Option<&Knockback>), With<Player>>,
+) {
+    for (mut animator, knockback) in player_query.iter_mut() {
+        let clip = if knockback.is_some() {
+            PLAYER_HIT
+        } else if *player_input != PlayerInput::default() {
+            PLAYER_MOVING
+        } else {
+            PLAYER_IDLE
+        };
+        animator.play(clip);
+    }
+}
+
+pub fn animate_sprites(
+    time: Res<Time>,
+    library: Res<AnimationLibrary>,
+    atlases: Res<Assets<TextureAtlas>>,
+    mut animator_query: Query<(&mut Animator, &mut TextureAtlasSprite, &Handle<TextureAtlas>)>,
+) {
+    for (mut animator, mut sprite, atlas) in animator_query.iter_mut() {
+        let Some(clip) = library.clips.get(animator.clip) else {
+            continue;
+        };
+        let frame_time = Duration::from_secs_f32(1.0 / clip.fps);
+        if animator.timer.duration() != frame_time {
+            animator.timer = Timer::new(frame_time, TimerMode::Repeating);
+        }
+        animator.timer.tick(time.delta());
+
+        animator.frame = clip.advance(animator.frame, animator.timer.times_finished_this_tick() as usize);
+
+        // a clip pointing past the end of its sheet is skipped rather than crashing the renderer
+        let index = clip.frames[animator.frame];
+        let in_sheet = atlases.get(atlas).is_some_and(|atlas| index < atlas.len());
+        if in_sheet && sprite.index != index {
+            sprite.index = index;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(frames: usize, looping: bool) -> AnimationClip {
+        AnimationClip {
+            frames: (0..frames).collect(),
+            fps: 10.0,
+            looping,
+        }
+    }
+
+    #[test]
+    fn shipped_clips_are_valid() {
+        let shipped: AnimationLibrary = ron::from_str(include_str!("../assets/animations/star_collector.anim.ron")).unwrap();
+        assert_eq!(shipped.validate(), Ok(()));
+        for name in [PLAYER_IDLE, PLAYER_MOVING, PLAYER_HIT, ENEMY_PULSE, CHASER_SPIN, WANDERER_WOBBLE, DASHER_PULSE, STAR_SPIN] {
+            assert!(shipped.clips.contains_key(name), "no `{}` clip", name);
+        }
+    }
+
+    #[test]
+    fn looping_clips_wrap_around() {
+        let looping = clip(4, true);
+        assert_eq!(looping.advance(0, 0), 0);
+        assert_eq!(looping.advance(1, 2), 3);
+        assert_eq!(looping.advance(3, 1), 0);
+        // a slow frame can finish several frames in one tick
+        assert_eq!(looping.advance(2, 7), 1);
+    }
+
+    #[test]
+    fn clips_that_dont_loop_hold_their_last_frame() {
+        let once = clip(4, false);
+        assert_eq!(once.advance(1, 1), 2);
+        assert_eq!(once.advance(2, 5), 3);
+        assert_eq!(once.advance(3, 1), 3);
+    }
+
+    #[test]
+    fn a_frame_past_the_end_of_a_shorter_clip_starts_from_its_last() {
+        assert_eq!(clip(2, true).advance(5, 1), 0);
+        assert_eq!(clip(2, false).advance(5, 0), 1);
+    }
+}
@@ -0,0 +1,210 @@
+use std::path::Path;
+
+use bevy::{audio::{AudioSinkPlayback, Volume}, prelude::*};
+use game_common::settings::Settings;
+use rand::prelude::*;
+
+use crate::events::{EnemyBounced, PlayerHit, PowerUpCollected, StarCollected};
+use crate::states::AppState;
+
+// no track ships with the game, one dropped in here plays on a loop
+pub const MUSIC_PATH: &str = "audio/music.ogg";
+
+// plays the sound effects for gameplay events and anything else sent as PlaySfx.
+// it only listens, so the simulation runs the same with or without this plugin
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<AudioVolume>()
+        .init_resource::<Settings>()
+        .add_event::<PlaySfx>()
+        .add_systems(PreStartup, load_sfx_handles)
+        .add_systems(Startup, start_music)
+        .add_systems(OnEnter(AppState::Paused), pause_audio)
+        .add_systems(OnExit(AppState::Paused), resume_audio)
+        .add_systems(Update, ((apply_volume_settings, sfx_for_gameplay_events, play_sfx).chain(), apply_music_volume.after(apply_volume_settings)));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SfxKind {
+    EnemyBounce,
+    StarCollected,
+    PowerUpCollected,
+    PlayerHit,
+}
+
+impl SfxKind {
+    pub const ALL: [SfxKind; 4] = [SfxKind::EnemyBounce, SfxKind::StarCollected, SfxKind::PowerUpCollected, SfxKind::PlayerHit];
+
+    // how many copies of this sound may play at once, extra requests are dropped
+    pub fn max_concurrent(&self) -> usize {
+        match self {
+            SfxKind::EnemyBounce => 4,
+            SfxKind::StarCollected => 3,
+            SfxKind::PowerUpCollected => 2,
+            SfxKind::PlayerHit => 2,
+        }
+    }
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlaySfx(pub SfxKind);
+
+// every volume is 0.0 to 1.0, the channels are scaled by the master volume
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+pub struct AudioVolume {
+    pub master: f32,
+    pub sfx: f32,
+    pub music: f32,
+}
+
+impl Default for AudioVolume {
+    fn default() -> AudioVolume {
+        AudioVolume {
+            master: 1.0,
+            sfx: 1.0,
+            music: 0.6,
+        }
+    }
+}
+
+impl AudioVolume {
+    pub fn sfx_volume(&self) -> f32 {
+        (self.master * self.sfx).clamp(0.0, 1.0)
+    }
+
+    pub fn music_volume(&self) -> f32 {
+        (self.master * self.music).clamp(0.0, 1.0)
+    }
+}
+
+// loaded once at startup, a kind with several variants picks one at random each time
+#[derive(Resource)]
+pub struct SfxHandles {
+    pub enemy_bounce: Vec<Handle<AudioSource>>,
+    pub star_collected: Vec<Handle<AudioSource>>,
+    pub power_up_collected: Vec<Handle<AudioSource>>,
+    pub player_hit: Vec<Handle<AudioSource>>,
+}
+
+impl SfxHandles {
+    pub fn variants(&self, kind: SfxKind) -> &[Handle<AudioSource>] {
+        match kind {
+            SfxKind::EnemyBounce => &self.enemy_bounce,
+            SfxKind::StarCollected => &self.star_collected,
+            SfxKind::PowerUpCollected => &self.power_up_collected,
+            SfxKind::PlayerHit => &self.player_hit,
+        }
+    }
+}
+
+// a sound effect that is still playing. Its entity despawns itself once the sound finishes
+#[derive(Component)]
+pub struct SfxInstance(pub SfxKind);
+
+// looping background track, its volume follows AudioVolume::music
+#[derive(Component)]
+pub struct Music;
+
+pub fn load_sfx_handles(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SfxHandles {
+        enemy_bounce: vec![
+            asset_server.load("audio/pluck_001.ogg"),
+            asset_server.load("audio/pluck_002.ogg"),
+        ],
+        star_collected: vec![asset_server.load("audio/impactMetal_000.ogg")],
+        power_up_collected: vec![asset_server.load("audio/impactMetal_000.ogg")],
+        player_hit: vec![asset_server.load("audio/explosionCrunch_000.ogg")],
+    });
+}
+
+pub fn start_music(mut commands: Commands, asset_server: Res<AssetServer>, volume: Res<AudioVolume>) {
+    if !asset_server.asset_io().is_file(Path::new(MUSIC_PATH)) {
+        return;
+    }
+    commands.spawn((
+        AudioBundle {
+            source: asset_server.load(MUSIC_PATH),
+            settings: PlaybackSettings::LOOP.with_volume(Volume::new_relative(volume.music_volume())),
+        },
+        Music,
+    ));
+}
+
+pub fn sfx_for_gameplay_events(
+    mut collected: EventReader<StarCollected>,
+    mut hits: EventReader<PlayerHit>,
+    mut bounced: EventReader<EnemyBounced>,
+    mut power_ups: EventReader<PowerUpCollected>,
+    mut sfx: EventWriter<PlaySfx>,
+) {
+    sfx.send_batch(collected.iter().map(|_| PlaySfx(SfxKind::StarCollected)));
+    sfx.send_batch(hits.iter().map(|_| PlaySfx(SfxKind::PlayerHit)));
+    sfx.send_batch(bounced.iter().map(|_| PlaySfx(SfxKind::EnemyBounce)));
+    sfx.send_batch(power_ups.iter().map(|_| PlaySfx(SfxKind::PowerUpCollected)));
+}
+
+pub fn play_sfx(
+    mut commands: Commands,
+    mut sfx_events: EventReader<PlaySfx>,
+    sfx_handles: Res<SfxHandles>,
+    volume: Res<AudioVolume>,
+    playing_query: Query<&SfxInstance>,
+) {
+    let mut playing = [0usize; SfxKind::ALL.len()];
+    for instance in playing_query.iter() {
+        playing[instance.0 as usize] += 1;
+    }
+    // which pluck plays is cosmetic, so it doesn't draw from the simulation rng
+    let mut rng = thread_rng();
+    for PlaySfx(kind) in sfx_events.iter() {
+        if playing[*kind as usize] >= kind.max_concurrent() {
+            continue;
+        }
+        let Some(source) = sfx_handles.variants(*kind).choose(&mut rng) else {
+            continue;
+        };
+        playing[*kind as usize] += 1;
+        commands.spawn((
+            AudioBundle {
+                source: source.clone(),
+                settings: PlaybackSettings::DESPAWN.with_volume(Volume::new_relative(volume.sfx_volume())),
+            },
+            SfxInstance(*kind),
+        ));
+    }
+}
+
+// master and effects volume come from the settings screen, music keeps its own level
+pub fn apply_volume_settings(settings: Res<Settings>, mut volume: ResMut<AudioVolume>) {
+    if !settings.is_changed() {
+        return;
+    }
+    volume.master = settings.master_volume;
+    volume.sfx = settings.sfx_volume;
+}
+
+pub fn apply_music_volume(volume: Res<AudioVolume>, music_query: Query<&AudioSink, With<Music>>) {
+    if !volume.is_changed() {
+        return;
+    }
+    for sink in music_query.iter() {
+        sink.set_volume(volume.music_volume());
+    }
+}
+
+// music and any sound effects still ringing out hold their place while the game is paused
+pub fn pause_audio(sink_query: Query<&AudioSink>) {
+    for sink in sink_query.iter() {
+        sink.pause();
+    }
+}
+
+pub fn resume_audio(sink_query: Query<&AudioSink>) {
+    for sink in sink_query.iter() {
+        sink.play();
+    }
+}
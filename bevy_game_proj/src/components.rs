@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+use game_common::collision::CollisionLayers;
+
+use crate::enemies::EnemyKind;
+
+// collision layers. Stars and power-ups share the pickup layer, they only ever touch the player
+pub const PLAYER_LAYER: u32 = 1 << 0;
+pub const ENEMY_LAYER: u32 = 1 << 1;
+pub const PICKUP_LAYER: u32 = 1 << 2;
+pub const PLAYER_COLLISION: CollisionLayers = CollisionLayers::new(PLAYER_LAYER, ENEMY_LAYER | PICKUP_LAYER);
+pub const ENEMY_COLLISION: CollisionLayers = CollisionLayers::new(ENEMY_LAYER, PLAYER_LAYER | ENEMY_LAYER);
+pub const PICKUP_COLLISION: CollisionLayers = CollisionLayers::new(PICKUP_LAYER, PLAYER_LAYER);
+
+#[derive(Component)]
+pub struct Player{}
+
+#[derive(Component)]
+pub struct Lives{
+    pub remaining: u32,
+}
+
+// added after the player is hit, removed once the timer runs out
+#[derive(Component)]
+pub struct Invulnerable{
+    pub timer: Timer,
+}
+
+#[derive(Component)]
+pub struct Knockback{
+    pub velocity: Vec2,
+}
+
+#[derive(Component)]
+pub struct Enemy{
+    pub direction: Vec2,
+    pub kind: EnemyKind,
+    pub speed: f32,
+}
+
+#[derive(Component)]
+pub struct Star{}
+
+// counts down to removal, fading the sprite out over the last `fade_out` seconds
+#[derive(Component)]
+pub struct Lifetime{
+    pub timer: Timer,
+    pub fade_out: f32,
+}
@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::difficulty::DifficultyRamp;
+use crate::resources::{EnemySpawnTimer, StarSpawnTimer};
+use crate::ron_asset::{apply_ron_asset, RonAsset, RonAssetPlugin};
+use crate::{
+    ENEMY_SPAWN_TIME, ENEMY_SPEED, INVULNERABILITY_TIME, KNOCKBACK_DAMPING, KNOCKBACK_SPEED, MAX_ENEMIES, MAX_PARTICLES, MAX_STARS,
+    NUMBER_OF_STARS, PLAYER_LIVES, PLAYER_SPEED, STAR_FADE_TIME, STAR_LIFETIME, STAR_SPAWN_TIME,
+};
+
+pub const GAME_CONFIG_PATH: &str = "config/game.config.ron";
+
+// loads the tuning file from the assets folder and re-applies it whenever it changes on disk.
+// without this plugin (e.g. headless) the simulation runs on the built in defaults
+pub struct GameConfigPlugin;
+
+impl Plugin for GameConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .add_plugins(RonAssetPlugin::<GameConfig>::new(GAME_CONFIG_PATH).held_during_replays())
+        .add_systems(Update, retime_spawn_timers.after(apply_ron_asset::<GameConfig>));
+    }
+}
+
+// gameplay tuning. Any field left out of the file keeps its default.
+// the other enemy kinds keep their speed relative to `enemy_speed`, and the difficulty presets
+// keep their spawn intervals relative to `enemy_spawn_time`
+#[derive(Resource, Serialize, Deserialize, TypeUuid, TypePath, Clone, PartialEq, Debug)]
+#[uuid = "5b0f3c8e-2d7a-4c1e-9f3b-8a6d4e2c1b70"]
+#[serde(default)]
+pub struct GameConfig {
+    pub player_speed: f32,
+    pub enemy_speed: f32,
+    pub number_of_stars: usize,
+    pub star_spawn_time: f32,
+    pub enemy_spawn_time: f32,
+    pub player_lives: u32,
+    pub invulnerability_time: f32,
+    pub knockback_speed: f32,
+    pub knockback_damping: f32,
+    pub max_enemies: usize,
+    pub max_stars: usize,
+    pub star_lifetime: f32,
+    pub star_fade_time: f32,
+    // particle budget, lower both on slow machines
+    pub max_particles: usize,
+    pub particle_density: f32,
+}
+
+impl Default for GameConfig {
+    fn default() -> GameConfig {
+        GameConfig {
+            player_speed: PLAYER_SPEED,
+            enemy_speed: ENEMY_SPEED,
+            number_of_stars: NUMBER_OF_STARS,
+            star_spawn_time: STAR_SPAWN_TIME,
+            enemy_spawn_time: ENEMY_SPAWN_TIME,
+            player_lives: PLAYER_LIVES,
+            invulnerability_time: INVULNERABILITY_TIME,
+            knockback_speed: KNOCKBACK_SPEED,
+            knockback_damping: KNOCKBACK_DAMPING,
+            max_enemies: MAX_ENEMIES,
+            max_stars: MAX_STARS,
+            star_lifetime: STAR_LIFETIME,
+            star_fade_time: STAR_FADE_TIME,
+            max_particles: MAX_PARTICLES,
+            particle_density: 1.0,
+        }
+    }
+}
+
+fn check_range<T: PartialOrd + std::fmt::Display>(errors: &mut Vec<String>, name: &str, value: T, min: T, max: T) {
+    let in_range = value >= min && value <= max;
+    if !in_range {
+        errors.push(format!("`{}` is {}, it must be between {} and {}", name, value, min, max));
+    }
+}
+
+impl RonAsset for GameConfig {
+    const NAME: &'static str = "game config";
+    const EXTENSIONS: &'static [&'static str] = &["config.ron"];
+
+    // every out of range field, not just the first, so one edit can fix them all
+    fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        check_range(&mut errors, "player_speed", self.player_speed, 1.0, 5000.0);
+        check_range(&mut errors, "enemy_speed", self.enemy_speed, 1.0, 5000.0);
+        check_range(&mut errors, "number_of_stars", self.number_of_stars, 0, 500);
+        check_range(&mut errors, "star_spawn_time", self.star_spawn_time, 0.05, 600.0);
+        check_range(&mut errors, "enemy_spawn_time", self.enemy_spawn_time, 0.05, 600.0);
+        check_range(&mut errors, "player_lives", self.player_lives, 1, 99);
+        check_range(&mut errors, "invulnerability_time", self.invulnerability_time, 0.0, 60.0);
+        check_range(&mut errors, "knockback_speed", self.knockback_speed, 0.0, 5000.0);
+        check_range(&mut errors, "knockback_damping", self.knockback_damping, 0.0, 100.0);
+        check_range(&mut errors, "max_enemies", self.max_enemies, 1, 500);
+        check_range(&mut errors, "max_stars", self.max_stars, 1, 500);
+        check_range(&mut errors, "star_lifetime", self.star_lifetime, 0.1, 600.0);
+        check_range(&mut errors, "star_fade_time", self.star_fade_time, 0.0, self.star_lifetime);
+        check_range(&mut errors, "max_particles", self.max_particles, 0, 10000);
+        check_range(&mut errors, "particle_density", self.particle_density, 0.0, 4.0);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
+
+impl GameConfig {
+    // how much faster or slower than the built in speeds the enemies move
+    pub fn enemy_speed_scale(&self) -> f32 {
+        self.enemy_speed / ENEMY_SPEED
+    }
+
+    pub fn enemy_spawn_time_scale(&self) -> f32 {
+        self.enemy_spawn_time / ENEMY_SPAWN_TIME
+    }
+}
+
+// the spawn timers are only rebuilt at the start of a run, so a new config retimes them directly
+pub fn retime_spawn_timers(
+    config: Res<GameConfig>,
+    ramp: Res<DifficultyRamp>,
+    mut star_spawn_timer: ResMut<StarSpawnTimer>,
+    mut enemy_spawn_timer: ResMut<EnemySpawnTimer>,
+) {
+    if !config.is_changed() {
+        return;
+    }
+    star_spawn_timer.timer.set_duration(Duration::from_secs_f32(config.star_spawn_time));
+    enemy_spawn_timer.timer.set_duration(Duration::from_secs_f32(ramp.enemy_spawn_interval * config.enemy_spawn_time_scale()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_and_shipped_file_are_valid() {
+        assert_eq!(GameConfig::default().validate(), Ok(()));
+        let shipped: GameConfig = ron::from_str(include_str!("../assets/config/game.config.ron")).unwrap();
+        assert_eq!(shipped.validate(), Ok(()));
+    }
+
+    #[test]
+    fn every_bad_field_is_reported() {
+        let config = GameConfig {
+            player_speed: 0.0,
+            max_stars: 0,
+            ..default()
+        };
+        let errors = config.validate().unwrap_err();
+        assert!(errors.contains("`player_speed` is 0"));
+        assert!(errors.contains("`max_stars` is 0"));
+    }
+
+    #[test]
+    fn star_fade_time_is_checked_against_the_lifetime() {
+        let config = GameConfig {
+            star_lifetime: 1.0,
+            star_fade_time: 2.0,
+            ..default()
+        };
+        assert!(config.validate().unwrap_err().contains("star_fade_time"));
+    }
+}
@@ -0,0 +1,288 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use game_common::score::Score;
+use serde::{Deserialize, Serialize};
+
+use crate::actions::Action;
+use crate::config::GameConfig;
+use crate::resources::{EnemySpawnTimer, SurvivalTime};
+use crate::states::{reset_run_resources, AppState, RunStart};
+use crate::{ENEMY_SPAWN_TIME, NUMBER_OF_ENEMIES};
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<Difficulty>()
+        .init_resource::<DifficultyRamp>()
+        .add_systems(OnEnter(AppState::MainMenu), spawn_difficulty_label)
+        .add_systems(OnExit(AppState::MainMenu), despawn_difficulty_label)
+        .add_systems(RunStart, reset_difficulty_ramp.after(reset_run_resources))
+        .add_systems(Update, (select_difficulty, update_difficulty_label).chain().run_if(in_state(AppState::MainMenu)));
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+// everything a preset decides. The ramp moves from the starting values to the caps as pressure builds
+pub struct DifficultySettings {
+    pub starting_enemies: usize,
+    pub enemy_spawn_interval: f32,
+    pub min_enemy_spawn_interval: f32,
+    pub enemy_speed_multiplier: f32,
+    pub max_enemy_speed_multiplier: f32,
+    pub ramp_per_second: f32,
+    pub ramp_per_star: f32,
+}
+
+impl DifficultySettings {
+    // how far a run with this much time survived and this score has pushed towards the caps
+    pub fn progress(&self, seconds: f32, score: u32) -> f32 {
+        (seconds * self.ramp_per_second + score as f32 * self.ramp_per_star).clamp(0.0, 1.0)
+    }
+}
+
+impl Difficulty {
+    pub fn settings(&self) -> DifficultySettings {
+        match self {
+            Difficulty::Easy => DifficultySettings {
+                starting_enemies: 3,
+                enemy_spawn_interval: 3.0,
+                min_enemy_spawn_interval: 1.5,
+                enemy_speed_multiplier: 0.8,
+                max_enemy_speed_multiplier: 1.3,
+                ramp_per_second: 1.0 / 300.0,
+                ramp_per_star: 1.0 / 150.0,
+            },
+            Difficulty::Normal => DifficultySettings {
+                starting_enemies: NUMBER_OF_ENEMIES,
+                enemy_spawn_interval: ENEMY_SPAWN_TIME,
+                min_enemy_spawn_interval: 0.8,
+                enemy_speed_multiplier: 1.0,
+                max_enemy_speed_multiplier: 1.6,
+                ramp_per_second: 1.0 / 180.0,
+                ramp_per_star: 1.0 / 80.0,
+            },
+            Difficulty::Hard => DifficultySettings {
+                starting_enemies: 6,
+                enemy_spawn_interval: 1.5,
+                min_enemy_spawn_interval: 0.5,
+                enemy_speed_multiplier: 1.2,
+                max_enemy_speed_multiplier: 2.0,
+                ramp_per_second: 1.0 / 120.0,
+                ramp_per_star: 1.0 / 50.0,
+            },
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    pub fn harder(&self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            _ => Difficulty::Hard,
+        }
+    }
+
+    pub fn easier(&self) -> Difficulty {
+        match self {
+            Difficulty::Hard => Difficulty::Normal,
+            _ => Difficulty::Easy,
+        }
+    }
+}
+
+// where the current run sits between the preset's starting values (progress 0) and its caps (progress 1)
+#[derive(Resource)]
+pub struct DifficultyRamp {
+    pub progress: f32,
+    pub enemy_spawn_interval: f32,
+    pub enemy_speed_multiplier: f32,
+}
+
+impl Default for DifficultyRamp {
+    fn default() -> DifficultyRamp {
+        DifficultyRamp::starting(&Difficulty::default().settings())
+    }
+}
+
+impl DifficultyRamp {
+    pub fn starting(settings: &DifficultySettings) -> DifficultyRamp {
+        DifficultyRamp::at(settings, 0.0)
+    }
+
+    pub fn at(settings: &DifficultySettings, progress: f32) -> DifficultyRamp {
+        DifficultyRamp {
+            progress,
+            enemy_spawn_interval: settings.enemy_spawn_interval
+                + (settings.min_enemy_spawn_interval - settings.enemy_spawn_interval) * progress,
+            enemy_speed_multiplier: settings.enemy_speed_multiplier
+                + (settings.max_enemy_speed_multiplier - settings.enemy_speed_multiplier) * progress,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct DifficultyLabel{}
+
+pub fn reset_difficulty_ramp(
+    difficulty: Res<Difficulty>,
+    config: Res<GameConfig>,
+    mut ramp: ResMut<DifficultyRamp>,
+    mut enemy_spawn_timer: ResMut<EnemySpawnTimer>,
+) {
+    let settings = difficulty.settings();
+    *ramp = DifficultyRamp::starting(&settings);
+    enemy_spawn_timer.timer.set_duration(Duration::from_secs_f32(settings.enemy_spawn_interval * config.enemy_spawn_time_scale()));
+    enemy_spawn_timer.timer.reset();
+}
+
+pub fn update_difficulty_ramp(
+    difficulty: Res<Difficulty>,
+    survival_time: Res<SurvivalTime>,
+    score: Res<Score>,
+    config: Res<GameConfig>,
+    mut ramp: ResMut<DifficultyRamp>,
+    mut enemy_spawn_timer: ResMut<EnemySpawnTimer>,
+) {
+    let settings = difficulty.settings();
+    let progress = settings.progress(survival_time.seconds, score.value);
+    if progress == ramp.progress {
+        return;
+    }
+    *ramp = DifficultyRamp::at(&settings, progress);
+    enemy_spawn_timer.timer.set_duration(Duration::from_secs_f32(ramp.enemy_spawn_interval * config.enemy_spawn_time_scale()));
+}
+
+pub fn select_difficulty(
+    actions: Res<Input<Action>>,
+    mut difficulty: ResMut<Difficulty>,
+) {
+    if actions.just_pressed(Action::MoveLeft) {
+        *difficulty = difficulty.easier();
+    }
+    if actions.just_pressed(Action::MoveRight) {
+        *difficulty = difficulty.harder();
+    }
+}
+
+fn difficulty_label_text(difficulty: &Difficulty) -> String {
+    format!("Difficulty: < {} >", difficulty.name())
+}
+
+pub fn spawn_difficulty_label(mut commands: Commands, difficulty: Res<Difficulty>) {
+    commands.spawn(
+        (
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(60.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            DifficultyLabel {},
+        )
+    ).with_children(|parent| {
+        parent.spawn(
+            TextBundle::from_section(
+                difficulty_label_text(&difficulty),
+                TextStyle {
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            )
+        );
+    });
+}
+
+pub fn update_difficulty_label(
+    difficulty: Res<Difficulty>,
+    label_query: Query<&Children, With<DifficultyLabel>>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !difficulty.is_changed() {
+        return;
+    }
+    for children in label_query.iter() {
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = difficulty_label_text(&difficulty);
+            }
+        }
+    }
+}
+
+pub fn despawn_difficulty_label(mut commands: Commands, label_query: Query<Entity, With<DifficultyLabel>>) {
+    for entity in label_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRESETS: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    #[test]
+    fn ramp_runs_from_the_starting_values_to_the_caps() {
+        for difficulty in PRESETS {
+            let settings = difficulty.settings();
+            let start = DifficultyRamp::at(&settings, 0.0);
+            assert_eq!(start.enemy_spawn_interval, settings.enemy_spawn_interval, "{:?}", difficulty);
+            assert_eq!(start.enemy_speed_multiplier, settings.enemy_speed_multiplier, "{:?}", difficulty);
+
+            let half = DifficultyRamp::at(&settings, 0.5);
+            assert!(half.enemy_spawn_interval < settings.enemy_spawn_interval, "{:?}", difficulty);
+            assert!(half.enemy_spawn_interval > settings.min_enemy_spawn_interval, "{:?}", difficulty);
+            assert!(half.enemy_speed_multiplier > settings.enemy_speed_multiplier, "{:?}", difficulty);
+            assert!(half.enemy_speed_multiplier < settings.max_enemy_speed_multiplier, "{:?}", difficulty);
+
+            let capped = DifficultyRamp::at(&settings, 1.0);
+            assert!((capped.enemy_spawn_interval - settings.min_enemy_spawn_interval).abs() < 1e-6, "{:?}", difficulty);
+            assert!((capped.enemy_speed_multiplier - settings.max_enemy_speed_multiplier).abs() < 1e-6, "{:?}", difficulty);
+        }
+    }
+
+    #[test]
+    fn progress_grows_with_time_and_score_and_stops_at_one() {
+        for difficulty in PRESETS {
+            let settings = difficulty.settings();
+            assert_eq!(settings.progress(0.0, 0), 0.0);
+            let early = settings.progress(10.0, 0);
+            assert!(early > 0.0 && early < 1.0, "{:?}", difficulty);
+            assert!(settings.progress(10.0, 5) > early, "{:?}", difficulty);
+            assert_eq!(settings.progress(1.0 / settings.ramp_per_second, 0), 1.0, "{:?}", difficulty);
+            assert_eq!(settings.progress(100_000.0, 10_000), 1.0, "{:?}", difficulty);
+        }
+    }
+
+    #[test]
+    fn harder_presets_start_and_cap_harder() {
+        for pair in PRESETS.windows(2) {
+            let (easier, harder) = (pair[0].settings(), pair[1].settings());
+            assert!(harder.enemy_spawn_interval < easier.enemy_spawn_interval);
+            assert!(harder.min_enemy_spawn_interval < easier.min_enemy_spawn_interval);
+            assert!(harder.enemy_speed_multiplier > easier.enemy_speed_multiplier);
+            assert!(harder.max_enemy_speed_multiplier > easier.max_enemy_speed_multiplier);
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use std::f32::consts::{FRAC_PI_4, TAU};
+
+use bevy::prelude::*;
+use game_common::{arena::ArenaBounds, collision::Collider};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::components::{Enemy, ENEMY_COLLISION};
+use crate::ENEMY_SIZE;
+
+pub const SAFE_SPAWN_DISTANCE: f32 = 250.0; // nothing spawns closer than this to the player
+pub const SAFE_SPAWN_ATTEMPTS: usize = 16;
+pub const TELEGRAPH_TIME: f32 = 0.8;
+pub const TELEGRAPH_BLINK_INTERVAL: f32 = 0.1;
+pub const RING_SIZE: usize = 4;
+pub const RING_RADIUS: f32 = 320.0;
+pub const CORNER_BURST_SIZE: usize = 3;
+pub const CORNER_BURST_SPREAD: f32 = 120.0;
+
+// how a group of enemies is placed on the field
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpawnPattern {
+    // anywhere outside the safe zone, used for the enemies a run starts with
+    Scatter,
+    // on a random wall, heading into the arena
+    EdgeEntry,
+    // evenly around the player, closing in
+    Ring,
+    // a cluster in one corner fanning out
+    CornerBurst,
+}
+
+impl SpawnPattern {
+    pub const TIMED: [SpawnPattern; 3] = [SpawnPattern::EdgeEntry, SpawnPattern::Ring, SpawnPattern::CornerBurst];
+
+    // relative chance of this pattern when the enemy spawn timer fires
+    pub fn spawn_weight(&self) -> u32 {
+        match self {
+            SpawnPattern::Scatter => 0,
+            SpawnPattern::EdgeEntry => 6,
+            SpawnPattern::Ring => 1,
+            SpawnPattern::CornerBurst => 2,
+        }
+    }
+
+    pub fn group_size(&self) -> usize {
+        match self {
+            SpawnPattern::Scatter | SpawnPattern::EdgeEntry => 1,
+            SpawnPattern::Ring => RING_SIZE,
+            SpawnPattern::CornerBurst => CORNER_BURST_SIZE,
+        }
+    }
+
+    pub fn random_timed(rng: &mut impl Rng) -> SpawnPattern {
+        let total: u32 = SpawnPattern::TIMED.iter().map(|pattern| pattern.spawn_weight()).sum();
+        let mut roll = rng.gen_range(0..total);
+        for pattern in SpawnPattern::TIMED {
+            if roll < pattern.spawn_weight() {
+                return pattern;
+            }
+            roll -= pattern.spawn_weight();
+        }
+        SpawnPattern::EdgeEntry
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SpawnPoint {
+    pub position: Vec2,
+    pub direction: Vec2,
+}
+
+// the enemy is visible but harmless and frozen in place until the timer runs out
+#[derive(Component)]
+pub struct Telegraph {
+    pub timer: Timer,
+}
+
+pub fn random_direction(rng: &mut impl Rng) -> Vec2 {
+    Vec2::from_angle(rng.gen::<f32>() * TAU)
+}
+
+fn is_safe(position: Vec2, player_position: Vec2) -> bool {
+    position.distance(player_position) >= SAFE_SPAWN_DISTANCE
+}
+
+fn clamp_to_arena(position: Vec2, arena: &ArenaBounds) -> Vec2 {
+    arena.clamp(position, Vec2::splat(ENEMY_SIZE / 2.0))
+}
+
+fn corners(arena: &ArenaBounds) -> [Vec2; 4] {
+    let margin = ENEMY_SIZE / 2.0;
+    [
+        Vec2::new(margin, margin),
+        Vec2::new(arena.width - margin, margin),
+        Vec2::new(margin, arena.height - margin),
+        Vec2::new(arena.width - margin, arena.height - margin),
+    ]
+}
+
+// a random point outside the safe zone. Falls back to the corner farthest from the player
+// when the arena is too small to find one
+fn safe_random_position(arena: &ArenaBounds, player_position: Vec2, rng: &mut impl Rng) -> Vec2 {
+    for _ in 0..SAFE_SPAWN_ATTEMPTS {
+        let position = clamp_to_arena(Vec2::new(rng.gen::<f32>() * arena.width, rng.gen::<f32>() * arena.height), arena);
+        if is_safe(position, player_position) {
+            return position;
+        }
+    }
+    corners(arena)
+        .into_iter()
+        .max_by(|a, b| a.distance(player_position).total_cmp(&b.distance(player_position)))
+        .unwrap()
+}
+
+// where and in which direction each enemy of a group should appear
+pub fn plan_spawns(
+    pattern: SpawnPattern,
+    count: usize,
+    arena: &ArenaBounds,
+    player_position: Vec2,
+    rng: &mut impl Rng,
+) -> Vec<SpawnPoint> {
+    let mut points = Vec::with_capacity(count);
+    match pattern {
+        SpawnPattern::Scatter => {
+            for _ in 0..count {
+                points.push(SpawnPoint {
+                    position: safe_random_position(arena, player_position, rng),
+                    direction: random_direction(rng),
+                });
+            }
+        }
+        SpawnPattern::EdgeEntry => {
+            for _ in 0..count {
+                let along = rng.gen::<f32>();
+                let (position, inward) = match rng.gen_range(0..4) {
+                    0 => (Vec2::new(along * arena.width, 0.0), Vec2::Y),
+                    1 => (Vec2::new(along * arena.width, arena.height), Vec2::NEG_Y),
+                    2 => (Vec2::new(0.0, along * arena.height), Vec2::X),
+                    _ => (Vec2::new(arena.width, along * arena.height), Vec2::NEG_X),
+                };
+                let mut position = clamp_to_arena(position, arena);
+                if !is_safe(position, player_position) {
+                    position = safe_random_position(arena, player_position, rng);
+                }
+                let spread = rng.gen_range(-FRAC_PI_4..=FRAC_PI_4);
+                points.push(SpawnPoint {
+                    position,
+                    direction: Vec2::from_angle(spread).rotate(inward),
+                });
+            }
+        }
+        SpawnPattern::Ring => {
+            let phase = rng.gen::<f32>() * TAU;
+            for index in 0..count {
+                let angle = phase + TAU * index as f32 / count as f32;
+                let mut position = clamp_to_arena(player_position + Vec2::from_angle(angle) * RING_RADIUS, arena);
+                // the wall can squash the ring into the safe zone
+                if !is_safe(position, player_position) {
+                    position = safe_random_position(arena, player_position, rng);
+                }
+                let direction = (player_position - position).try_normalize().unwrap_or_else(|| random_direction(rng));
+                points.push(SpawnPoint { position, direction });
+            }
+        }
+        SpawnPattern::CornerBurst => {
+            let all_corners = corners(arena);
+            let safe_corners: Vec<Vec2> = all_corners.iter().copied().filter(|corner| is_safe(*corner, player_position)).collect();
+            let corner = if safe_corners.is_empty() {
+                safe_random_position(arena, player_position, rng)
+            } else {
+                safe_corners[rng.gen_range(0..safe_corners.len())]
+            };
+            let inward = (arena.center() - corner).try_normalize().unwrap_or(Vec2::X);
+            for _ in 0..count {
+                let fan = Vec2::from_angle(rng.gen_range(-FRAC_PI_4..=FRAC_PI_4)).rotate(inward);
+                let mut position = clamp_to_arena(corner + fan * rng.gen::<f32>() * CORNER_BURST_SPREAD, arena);
+                if !is_safe(position, player_position) {
+                    position = corner;
+                }
+                points.push(SpawnPoint { position, direction: fan });
+            }
+        }
+    }
+    points
+}
+
+// blinks telegraphing enemies and makes them solid once the warning is over
+pub fn tick_telegraphs(
+    mut commands: Commands,
+    fixed_time: Res<FixedTime>,
+    mut telegraph_query: Query<(Entity, &Enemy, &mut Telegraph, &mut TextureAtlasSprite)>,
+) {
+    for (entity, enemy, mut telegraph, mut sprite) in telegraph_query.iter_mut() {
+        telegraph.timer.tick(fixed_time.period);
+        if telegraph.timer.finished() {
+            sprite.color.set_a(1.0);
+            commands.entity(entity)
+                .remove::<Telegraph>()
+                .insert(Collider::circle(enemy.kind.size() / 2.0, ENEMY_COLLISION));
+        } else {
+            let blink = ((telegraph.timer.elapsed_secs() / TELEGRAPH_BLINK_INTERVAL) as u32).is_multiple_of(2);
+            sprite.color.set_a(if blink { 0.2 } else { 0.6 });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    const ALL_PATTERNS: [SpawnPattern; 4] = [
+        SpawnPattern::Scatter,
+        SpawnPattern::EdgeEntry,
+        SpawnPattern::Ring,
+        SpawnPattern::CornerBurst,
+    ];
+
+    #[test]
+    fn every_pattern_spawns_outside_the_safe_zone() {
+        let arena = ArenaBounds::default();
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        // the middle, hugging a wall and sitting in a corner
+        let player_positions = [
+            arena.center(),
+            Vec2::new(arena.width / 2.0, ENEMY_SIZE),
+            Vec2::new(ENEMY_SIZE, ENEMY_SIZE),
+            Vec2::new(arena.width - ENEMY_SIZE, arena.height - ENEMY_SIZE),
+        ];
+        for pattern in ALL_PATTERNS {
+            for player_position in player_positions {
+                for _ in 0..50 {
+                    let points = plan_spawns(pattern, pattern.group_size(), &arena, player_position, &mut rng);
+                    assert_eq!(points.len(), pattern.group_size());
+                    for point in points {
+                        assert!(
+                            point.position.distance(player_position) >= SAFE_SPAWN_DISTANCE,
+                            "{:?} spawned at {} with the player at {}",
+                            pattern, point.position, player_position
+                        );
+                        assert!(point.position.x >= 0.0 && point.position.x <= arena.width);
+                        assert!(point.position.y >= 0.0 && point.position.y <= arena.height);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn only_the_timed_patterns_are_rolled() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        for _ in 0..200 {
+            assert_ne!(SpawnPattern::random_timed(&mut rng), SpawnPattern::Scatter);
+        }
+    }
+}
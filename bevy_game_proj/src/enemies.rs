@@ -0,0 +1,380 @@
+use bevy::prelude::*;
+use game_common::{arena::ConfineToArena, collision::Contacts, movement::Velocity};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::animation::{Animator, CHASER_SPIN, DASHER_PULSE, ENEMY_PULSE, WANDERER_WOBBLE};
+use crate::components::{Enemy, Player};
+use crate::director::{Telegraph, TELEGRAPH_TIME};
+use crate::events::EnemySpawned;
+use crate::pool::{EntityPool, PoolKind};
+use crate::resources::{GameAssets, SimulationRng};
+use crate::{ENEMY_SIZE, ENEMY_SPEED};
+
+pub const CHASER_SPEED: f32 = 170.0;
+pub const CHASER_SIZE: f32 = 48.0;
+pub const CHASER_TURN_RATE: f32 = 1.8; // radians per second, so it can be outrun around corners
+pub const WANDERER_SPEED: f32 = 200.0;
+pub const WANDERER_SIZE: f32 = 56.0;
+pub const WANDERER_RETARGET_TIME: f32 = 1.0;
+pub const WANDERER_MAX_TURN: f32 = std::f32::consts::FRAC_PI_2;
+pub const WANDERER_STEERING: f32 = 2.5;
+pub const DASHER_LUNGE_SPEED: f32 = 750.0;
+pub const DASHER_SIZE: f32 = 40.0;
+pub const DASHER_WINDUP_TIME: f32 = 1.2;
+pub const DASHER_LUNGE_TIME: f32 = 0.35;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EnemyKind {
+    Bouncer,
+    Chaser,
+    Wanderer,
+    Dasher,
+}
+
+impl EnemyKind {
+    pub const ALL: [EnemyKind; 4] = [EnemyKind::Bouncer, EnemyKind::Chaser, EnemyKind::Wanderer, EnemyKind::Dasher];
+
+    pub fn speed(&self) -> f32 {
+        match self {
+            EnemyKind::Bouncer => ENEMY_SPEED,
+            EnemyKind::Chaser => CHASER_SPEED,
+            EnemyKind::Wanderer => WANDERER_SPEED,
+            EnemyKind::Dasher => DASHER_LUNGE_SPEED,
+        }
+    }
+
+    pub fn size(&self) -> f32 {
+        match self {
+            EnemyKind::Bouncer => ENEMY_SIZE,
+            EnemyKind::Chaser => CHASER_SIZE,
+            EnemyKind::Wanderer => WANDERER_SIZE,
+            EnemyKind::Dasher => DASHER_SIZE,
+        }
+    }
+
+    // each kind has its own row on the enemy sheet and its own clip playing it
+    pub fn clip(&self) -> &'static str {
+        match self {
+            EnemyKind::Bouncer => ENEMY_PULSE,
+            EnemyKind::Chaser => CHASER_SPIN,
+            EnemyKind::Wanderer => WANDERER_WOBBLE,
+            EnemyKind::Dasher => DASHER_PULSE,
+        }
+    }
+
+    // relative chance of this kind being picked when a random enemy spawns
+    pub fn spawn_weight(&self) -> u32 {
+        match self {
+            EnemyKind::Bouncer => 4,
+            EnemyKind::Chaser => 2,
+            EnemyKind::Wanderer => 3,
+            EnemyKind::Dasher => 1,
+        }
+    }
+
+    pub fn random(rng: &mut impl Rng) -> EnemyKind {
+        let total: u32 = EnemyKind::ALL.iter().map(|kind| kind.spawn_weight()).sum();
+        let mut roll = rng.gen_range(0..total);
+        for kind in EnemyKind::ALL {
+            if roll < kind.spawn_weight() {
+                return kind;
+            }
+            roll -= kind.spawn_weight();
+        }
+        EnemyKind::Bouncer
+    }
+}
+
+// steers toward the player, but can only turn so fast
+#[derive(Component)]
+pub struct Chaser{}
+
+// drifts toward a heading that changes a little every WANDERER_RETARGET_TIME
+#[derive(Component)]
+pub struct Wanderer{
+    pub target_direction: Vec2,
+    pub retarget_timer: Timer,
+}
+
+// sits still aiming at the player, then lunges in a straight line
+#[derive(Component)]
+pub struct Dasher{
+    pub lunging: bool,
+    pub timer: Timer,
+}
+
+pub fn spawn_enemy_of_kind(
+    commands: &mut Commands,
+    pool: &mut EntityPool,
+    game_assets: &GameAssets,
+    kind: EnemyKind,
+    position: Vec2,
+    direction: Vec2,
+    spawned: &mut EventWriter<EnemySpawned>,
+) {
+    let speed = match kind {
+        EnemyKind::Dasher => 0.0,
+        _ => kind.speed(),
+    };
+    let mut enemy = pool.acquire(commands, PoolKind::Enemy);
+    enemy.insert(
+        (
+            SpriteSheetBundle{
+                sprite: TextureAtlasSprite {
+                    custom_size: Some(Vec2::splat(kind.size())),
+                    ..default()
+                },
+                transform: Transform::from_xyz(position.x, position.y, 0.0),
+                texture_atlas: game_assets.enemy_atlas.clone(),
+                ..default()
+            },
+            Animator::new(kind.clip()),
+            Velocity::default(),
+            ConfineToArena::square(kind.size()),
+            Enemy{
+                direction,
+                kind,
+                speed,
+            },
+            Telegraph {
+                timer: Timer::from_seconds(TELEGRAPH_TIME, TimerMode::Once),
+            },
+        )
+    );
+    match kind {
+        EnemyKind::Bouncer => {}
+        EnemyKind::Chaser => {
+            enemy.insert(Chaser {});
+        }
+        EnemyKind::Wanderer => {
+            enemy.insert(Wanderer {
+                target_direction: direction,
+                retarget_timer: Timer::from_seconds(WANDERER_RETARGET_TIME, TimerMode::Repeating),
+            });
+        }
+        EnemyKind::Dasher => {
+            enemy.insert(Dasher {
+                lunging: false,
+                timer: Timer::from_seconds(DASHER_WINDUP_TIME, TimerMode::Once),
+            });
+        }
+    }
+    spawned.send(EnemySpawned {
+        enemy: enemy.id(),
+        kind,
+        position,
+    });
+}
+
+pub fn steer_chasers(
+    mut chaser_query: Query<(&Transform, &mut Enemy), With<Chaser>>,
+    player_query: Query<&Transform, With<Player>>,
+    fixed_time: Res<FixedTime>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let max_turn = CHASER_TURN_RATE * fixed_time.period.as_secs_f32();
+    for (transform, mut enemy) in chaser_query.iter_mut() {
+        let to_player = (player_transform.translation - transform.translation).truncate();
+        if to_player.length_squared() == 0.0 {
+            continue;
+        }
+        let turn = enemy.direction.angle_between(to_player).clamp(-max_turn, max_turn);
+        let heading = enemy.direction.y.atan2(enemy.direction.x) + turn;
+        enemy.direction = Vec2::from_angle(heading);
+    }
+}
+
+pub fn steer_wanderers(
+    mut wanderer_query: Query<(&mut Enemy, &mut Wanderer)>,
+    mut rng: ResMut<SimulationRng>,
+    fixed_time: Res<FixedTime>,
+) {
+    let delta = fixed_time.period.as_secs_f32();
+    for (mut enemy, mut wanderer) in wanderer_query.iter_mut() {
+        wanderer.retarget_timer.tick(fixed_time.period);
+        if wanderer.retarget_timer.just_finished() {
+            let turn = rng.gen_range(-WANDERER_MAX_TURN..=WANDERER_MAX_TURN);
+            wanderer.target_direction = Vec2::from_angle(turn).rotate(enemy.direction);
+        }
+        let steered = enemy.direction.lerp(wanderer.target_direction, (WANDERER_STEERING * delta).min(1.0));
+        if steered.length_squared() > 0.0 {
+            enemy.direction = steered.normalize();
+        }
+    }
+}
+
+pub fn update_dashers(
+    mut dasher_query: Query<(&Transform, &mut Enemy, &mut Dasher)>,
+    player_query: Query<&Transform, With<Player>>,
+    fixed_time: Res<FixedTime>,
+) {
+    let player_position = player_query.get_single().ok().map(|transform| transform.translation.truncate());
+    for (transform, mut enemy, mut dasher) in dasher_query.iter_mut() {
+        dasher.timer.tick(fixed_time.period);
+        if !dasher.timer.finished() {
+            continue;
+        }
+        if dasher.lunging {
+            dasher.lunging = false;
+            dasher.timer = Timer::from_seconds(DASHER_WINDUP_TIME, TimerMode::Once);
+            enemy.speed = 0.0;
+        } else {
+            if let Some(player_position) = player_position {
+                let to_player = player_position - transform.translation.truncate();
+                if to_player.length_squared() > 0.0 {
+                    enemy.direction = to_player.normalize();
+                }
+            }
+            dasher.lunging = true;
+            dasher.timer = Timer::from_seconds(DASHER_LUNGE_TIME, TimerMode::Once);
+            enemy.speed = enemy.kind.speed();
+        }
+    }
+}
+
+// elastic bounce between overlapping enemies, heavier (bigger) enemies get pushed around less.
+// Each enemy keeps the speed of its kind, only its direction changes
+pub fn enemy_enemy_collision(
+    contacts: Res<Contacts>,
+    mut enemy_query: Query<(&mut Transform, &mut Enemy)>,
+) {
+    for &(a, b) in contacts.pairs() {
+        // the other contacts involve the player or a pickup
+        let Ok([(mut transform_a, mut enemy_a), (mut transform_b, mut enemy_b)]) = enemy_query.get_many_mut([a, b]) else {
+            continue;
+        };
+        let radius_a = enemy_a.kind.size() / 2.0;
+        let radius_b = enemy_b.kind.size() / 2.0;
+        let delta = (transform_b.translation - transform_a.translation).truncate();
+        let distance = delta.length();
+        let overlap = radius_a + radius_b - distance;
+        // an earlier pair this tick may already have pushed them apart
+        if overlap <= 0.0 {
+            continue;
+        }
+        let normal = if distance > 0.0 { delta / distance } else { Vec2::X };
+        let mass_a = radius_a * radius_a;
+        let mass_b = radius_b * radius_b;
+        let total_mass = mass_a + mass_b;
+
+        transform_a.translation -= (normal * overlap * mass_b / total_mass).extend(0.0);
+        transform_b.translation += (normal * overlap * mass_a / total_mass).extend(0.0);
+
+        let velocity_a = enemy_a.direction * enemy_a.speed;
+        let velocity_b = enemy_b.direction * enemy_b.speed;
+        let approach_speed = (velocity_a - velocity_b).dot(normal);
+        if approach_speed <= 0.0 {
+            continue;
+        }
+        let impulse = 2.0 * approach_speed / total_mass;
+        let new_velocity_a = velocity_a - normal * impulse * mass_b;
+        let new_velocity_b = velocity_b + normal * impulse * mass_a;
+        if new_velocity_a.length_squared() > 0.0 {
+            enemy_a.direction = new_velocity_a.normalize();
+        }
+        if new_velocity_b.length_squared() > 0.0 {
+            enemy_b.direction = new_velocity_b.normalize();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FIXED_TIMESTEP;
+
+    fn world_with_player(player: Vec2) -> World {
+        let mut world = World::new();
+        world.insert_resource(FixedTime::new_from_secs(FIXED_TIMESTEP));
+        world.insert_resource(SimulationRng::new(Some(7)));
+        world.spawn((Player {}, Transform::from_translation(player.extend(0.0))));
+        world
+    }
+
+    fn enemy(kind: EnemyKind, direction: Vec2) -> (Transform, Enemy) {
+        (Transform::default(), Enemy { direction, kind, speed: kind.speed() })
+    }
+
+    fn run_ticks<M>(world: &mut World, ticks: usize, system: impl IntoSystemConfigs<M>) {
+        let mut schedule = Schedule::new();
+        schedule.add_systems(system);
+        for _ in 0..ticks {
+            schedule.run(world);
+        }
+    }
+
+    fn direction(world: &World, entity: Entity) -> Vec2 {
+        world.get::<Enemy>(entity).unwrap().direction
+    }
+
+    #[test]
+    fn every_kind_has_its_own_clip() {
+        for (index, kind) in EnemyKind::ALL.iter().enumerate() {
+            assert!(EnemyKind::ALL[index + 1..].iter().all(|other| other.clip() != kind.clip()));
+        }
+    }
+
+    #[test]
+    fn chasers_turn_towards_the_player_no_faster_than_their_turn_rate() {
+        let mut world = world_with_player(Vec2::new(0.0, 100.0));
+        let chaser = world.spawn((enemy(EnemyKind::Chaser, Vec2::X), Chaser {})).id();
+
+        run_ticks(&mut world, 1, steer_chasers);
+        let turned = Vec2::X.angle_between(direction(&world, chaser));
+        assert!((turned - CHASER_TURN_RATE * FIXED_TIMESTEP).abs() < 1e-4, "turned {}", turned);
+
+        // a quarter turn takes a little under a second
+        run_ticks(&mut world, 60, steer_chasers);
+        assert!((direction(&world, chaser) - Vec2::Y).length() < 1e-3);
+    }
+
+    #[test]
+    fn wanderers_drift_towards_a_heading_near_their_own() {
+        let mut world = world_with_player(Vec2::ZERO);
+        let wanderer = world.spawn((
+            enemy(EnemyKind::Wanderer, Vec2::X),
+            Wanderer {
+                target_direction: Vec2::X,
+                retarget_timer: Timer::from_seconds(WANDERER_RETARGET_TIME, TimerMode::Repeating),
+            },
+        )).id();
+
+        for _ in 0..10 {
+            run_ticks(&mut world, (WANDERER_RETARGET_TIME / FIXED_TIMESTEP).round() as usize, steer_wanderers);
+            let heading = direction(&world, wanderer);
+            let target = world.get::<Wanderer>(wanderer).unwrap().target_direction;
+            assert!((heading.length() - 1.0).abs() < 1e-4);
+            // the target was picked this tick within WANDERER_MAX_TURN of the old heading, which has
+            // already turned a little towards it
+            assert!(heading.angle_between(target).abs() <= WANDERER_MAX_TURN + 1e-4);
+        }
+    }
+
+    #[test]
+    fn dashers_wind_up_then_lunge_at_the_player_and_stop() {
+        let mut world = world_with_player(Vec2::new(0.0, -100.0));
+        let (transform, mut dasher_enemy) = enemy(EnemyKind::Dasher, Vec2::X);
+        dasher_enemy.speed = 0.0;
+        let dasher = world.spawn((
+            transform,
+            dasher_enemy,
+            Dasher { lunging: false, timer: Timer::from_seconds(DASHER_WINDUP_TIME, TimerMode::Once) },
+        )).id();
+        let ticks = |seconds: f32| (seconds / FIXED_TIMESTEP).ceil() as usize;
+
+        run_ticks(&mut world, ticks(DASHER_WINDUP_TIME) - 1, update_dashers);
+        assert_eq!(world.get::<Enemy>(dasher).unwrap().speed, 0.0);
+
+        run_ticks(&mut world, 1, update_dashers);
+        assert!(world.get::<Dasher>(dasher).unwrap().lunging);
+        assert_eq!(world.get::<Enemy>(dasher).unwrap().speed, DASHER_LUNGE_SPEED);
+        assert!((direction(&world, dasher) - Vec2::NEG_Y).length() < 1e-6);
+
+        run_ticks(&mut world, ticks(DASHER_LUNGE_TIME), update_dashers);
+        assert!(!world.get::<Dasher>(dasher).unwrap().lunging);
+        assert_eq!(world.get::<Enemy>(dasher).unwrap().speed, 0.0);
+    }
+}
@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+
+use crate::enemies::EnemyKind;
+use crate::powerups::PowerUpKind;
+
+// what happened during a tick, sent by the simulation in a reproducible order.
+// score, audio, particles, the hud and the run stats each listen for the ones they care about,
+// so hooking in something new doesn't mean touching the collision code
+
+// the star has already been handed back to the pool when this is read
+#[derive(Event, Clone, Copy, Debug)]
+pub struct StarCollected {
+    pub star: Entity,
+    pub position: Vec2,
+}
+
+// sent for every hit, including the one that takes the last life
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlayerHit {
+    pub position: Vec2,
+    // a shield soaked up the hit, no life was lost
+    pub shielded: bool,
+    pub lives_left: u32,
+}
+
+// the power-up is already running and its pickup despawned when this is read
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PowerUpCollected {
+    pub kind: PowerUpKind,
+    pub position: Vec2,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlayerDied {
+    pub player: Entity,
+    pub position: Vec2,
+}
+
+// an enemy bounced off the edge of the arena
+#[derive(Event, Clone, Copy, Debug)]
+pub struct EnemyBounced {
+    pub enemy: Entity,
+    // where it touched the wall
+    pub position: Vec2,
+    // points away from the wall(s) it hit
+    pub wall_normal: Vec2,
+}
+
+// sent as the enemy appears, before its telegraph has run out
+#[derive(Event, Clone, Copy, Debug)]
+pub struct EnemySpawned {
+    pub enemy: Entity,
+    pub kind: EnemyKind,
+    pub position: Vec2,
+}
@@ -0,0 +1,390 @@
+use std::{cmp::Reverse, fs, path::{Path, PathBuf}};
+
+use bevy::prelude::*;
+use game_common::{ron_file::{read_ron_file, write_ron_file, RonFileError}, score::Score};
+use serde::{Deserialize, Serialize};
+
+use crate::actions::Action;
+use crate::resources::SurvivalTime;
+use crate::states::{start_game, AppState};
+use crate::systems::exit_game;
+
+pub const MAX_HIGH_SCORES: usize = 10;
+pub const MAX_INITIALS: usize = 3;
+// the letters the gamepad picker steps through
+pub const INITIALS_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+// set this to store the table somewhere other than the platform data dir
+pub const HIGH_SCORE_PATH_ENV: &str = "STAR_COLLECTOR_HIGHSCORES";
+
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<HighScorePath>()
+        .init_resource::<HighScores>()
+        .init_resource::<InitialsEntry>()
+        .add_systems(Startup, load_high_scores)
+        .add_systems(OnEnter(AppState::GameOver), (begin_initials_entry, spawn_high_score_panel).chain())
+        .add_systems(OnExit(AppState::GameOver), despawn_high_score_panel)
+        .add_systems(Update, (enter_initials.before(start_game).before(exit_game), update_high_score_panel).chain().run_if(in_state(AppState::GameOver)));
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HighScoreEntry {
+    pub initials: String,
+    pub score: u32,
+    pub survival_seconds: f32,
+}
+
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+pub struct HighScores {
+    pub entries: Vec<HighScoreEntry>,
+}
+
+impl HighScores {
+    pub fn qualifies(&self, score: u32) -> bool {
+        if score == 0 {
+            return false;
+        }
+        self.entries.len() < MAX_HIGH_SCORES
+            || self.entries.last().is_none_or(|lowest| score > lowest.score)
+    }
+
+    pub fn insert(&mut self, entry: HighScoreEntry) {
+        self.entries.push(entry);
+        // stable sort, so an older entry stays ahead of a new one with the same score
+        self.entries.sort_by_key(|entry| Reverse(entry.score));
+        self.entries.truncate(MAX_HIGH_SCORES);
+    }
+}
+
+#[derive(Resource)]
+pub struct HighScorePath {
+    pub path: PathBuf,
+}
+
+impl Default for HighScorePath {
+    fn default() -> HighScorePath {
+        let path = match std::env::var_os(HIGH_SCORE_PATH_ENV) {
+            Some(path) => PathBuf::from(path),
+            None => dirs::data_dir()
+                .map(|dir| dir.join("bevy_game_proj"))
+                .unwrap_or_default()
+                .join("highscores.ron"),
+        };
+        HighScorePath { path }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct InitialsEntry {
+    pub active: bool,
+    pub initials: String,
+    // keys still held from the run, which type nothing until they are let go so that
+    // key repeat on a movement key doesn't fill in the initials
+    pub held_keys: Vec<KeyCode>,
+    // index into INITIALS_ALPHABET of the letter the picker is on
+    pub picked: usize,
+    // set once anything is typed on the keyboard, after which the picker is out of the way
+    pub typed: bool,
+}
+
+impl InitialsEntry {
+    pub fn picked_letter(&self) -> char {
+        INITIALS_ALPHABET[self.picked] as char
+    }
+}
+
+// true when the plugin isn't installed at all, so headless apps can restart freely
+pub fn initials_entry_finished(initials_entry: Option<Res<InitialsEntry>>) -> bool {
+    initials_entry.is_none_or(|initials_entry| !initials_entry.active)
+}
+
+#[derive(Component)]
+pub struct HighScorePanel{}
+
+pub fn read_high_scores(path: &Path) -> HighScores {
+    match read_ron_file::<HighScores>(path) {
+        Ok(Some(mut high_scores)) => {
+            high_scores.entries.sort_by_key(|entry| Reverse(entry.score));
+            high_scores.entries.truncate(MAX_HIGH_SCORES);
+            high_scores
+        }
+        Ok(None) => HighScores::default(),
+        Err(RonFileError::Parse(err)) => {
+            // keep the broken file around instead of overwriting it on the next save
+            let backup = path.with_extension("ron.corrupt");
+            warn!("high score file {:?} is corrupt ({}), moving it to {:?}", path, err, backup);
+            if let Err(err) = fs::rename(path, &backup) {
+                warn!("could not move corrupt high score file: {}", err);
+            }
+            HighScores::default()
+        }
+        Err(err) => {
+            warn!("could not read high score file {:?}: {}", path, err);
+            HighScores::default()
+        }
+    }
+}
+
+pub fn write_high_scores(path: &Path, high_scores: &HighScores) {
+    if let Err(err) = write_ron_file(path, high_scores) {
+        warn!("could not write high score file {:?}: {}", path, err);
+    }
+}
+
+pub fn load_high_scores(
+    high_score_path: Res<HighScorePath>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    *high_scores = read_high_scores(&high_score_path.path);
+}
+
+pub fn begin_initials_entry(
+    score: Res<Score>,
+    high_scores: Res<HighScores>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut received_characters: ResMut<Events<ReceivedCharacter>>,
+    mut initials_entry: ResMut<InitialsEntry>,
+) {
+    initials_entry.active = high_scores.qualifies(score.value);
+    initials_entry.initials.clear();
+    initials_entry.held_keys = keyboard_input.get_pressed().copied().collect();
+    initials_entry.picked = 0;
+    initials_entry.typed = false;
+    // whatever was typed while playing isn't meant for the initials
+    received_characters.clear();
+}
+
+// typed on the keyboard, or picked a letter at a time with up/down and Confirm so a gamepad
+// can do it too. Quit deletes a letter, or skips saving when there are none left
+pub fn enter_initials(
+    mut received_characters: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut actions: ResMut<Input<Action>>,
+    score: Res<Score>,
+    survival_time: Res<SurvivalTime>,
+    high_score_path: Res<HighScorePath>,
+    mut high_scores: ResMut<HighScores>,
+    mut initials_entry: ResMut<InitialsEntry>,
+) {
+    if !initials_entry.held_keys.is_empty() {
+        initials_entry.held_keys.retain(|&key| keyboard_input.pressed(key));
+    }
+    if !initials_entry.active || !initials_entry.held_keys.is_empty() {
+        received_characters.clear();
+        return;
+    }
+    for event in received_characters.iter() {
+        if event.char.is_ascii_alphanumeric() && initials_entry.initials.len() < MAX_INITIALS {
+            initials_entry.initials.push(event.char.to_ascii_uppercase());
+            initials_entry.typed = true;
+        }
+    }
+
+    // movement keys are letters too, so they only drive the picker until something is typed
+    let full = initials_entry.initials.len() >= MAX_INITIALS;
+    // taken so the same press doesn't also start the next run
+    let confirmed = actions.clear_just_pressed(Action::Confirm);
+    if !initials_entry.typed {
+        let letters = INITIALS_ALPHABET.len();
+        if actions.just_pressed(Action::MoveUp) {
+            initials_entry.picked = (initials_entry.picked + letters - 1) % letters;
+        }
+        if actions.just_pressed(Action::MoveDown) {
+            initials_entry.picked = (initials_entry.picked + 1) % letters;
+        }
+        if confirmed && !full {
+            let letter = initials_entry.picked_letter();
+            initials_entry.initials.push(letter);
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        initials_entry.initials.pop();
+    }
+    // taken so skipping doesn't also close the game
+    if actions.clear_just_pressed(Action::Quit) && initials_entry.initials.pop().is_none() {
+        initials_entry.active = false;
+        return;
+    }
+
+    let save = keyboard_input.just_pressed(KeyCode::Return) || (confirmed && (full || initials_entry.typed));
+    if save && !initials_entry.initials.is_empty() {
+        high_scores.insert(HighScoreEntry {
+            initials: initials_entry.initials.clone(),
+            score: score.value,
+            survival_seconds: survival_time.seconds,
+        });
+        write_high_scores(&high_score_path.path, &high_scores);
+        initials_entry.active = false;
+    }
+}
+
+fn high_score_panel_text(high_scores: &HighScores, initials_entry: &InitialsEntry) -> String {
+    let mut text = String::new();
+    if initials_entry.active {
+        // the letter the picker is on sits in brackets in the next free slot
+        let mut initials = initials_entry.initials.clone();
+        if initials.len() < MAX_INITIALS {
+            let remaining = MAX_INITIALS - initials.len();
+            if initials_entry.typed {
+                initials.push_str(&"_".repeat(remaining));
+            } else {
+                initials.push_str(&format!("[{}]", initials_entry.picked_letter()));
+                initials.push_str(&"_".repeat(remaining - 1));
+            }
+        }
+        text.push_str(&format!(
+            "New high score! Enter your initials: {}\n(type, or pick with up/down and Space. Enter to save, Backspace to delete, Esc to skip)\n\n",
+            initials
+        ));
+    }
+    text.push_str("High Scores\n");
+    if high_scores.entries.is_empty() {
+        text.push_str("none yet");
+    }
+    for (rank, entry) in high_scores.entries.iter().enumerate() {
+        text.push_str(&format!(
+            "{:>2}. {:<3} {:>5}  {:.0}s\n",
+            rank + 1,
+            entry.initials,
+            entry.score,
+            entry.survival_seconds
+        ));
+    }
+    text
+}
+
+pub fn spawn_high_score_panel(
+    mut commands: Commands,
+    high_scores: Res<HighScores>,
+    initials_entry: Res<InitialsEntry>,
+) {
+    commands.spawn(
+        (
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(20.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            HighScorePanel {},
+        )
+    ).with_children(|parent| {
+        parent.spawn(
+            TextBundle::from_section(
+                high_score_panel_text(&high_scores, &initials_entry),
+                TextStyle {
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ).with_text_alignment(TextAlignment::Center)
+        );
+    });
+}
+
+pub fn update_high_score_panel(
+    high_scores: Res<HighScores>,
+    initials_entry: Res<InitialsEntry>,
+    panel_query: Query<&Children, With<HighScorePanel>>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !high_scores.is_changed() && !initials_entry.is_changed() {
+        return;
+    }
+    for children in panel_query.iter() {
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = high_score_panel_text(&high_scores, &initials_entry);
+            }
+        }
+    }
+}
+
+pub fn despawn_high_score_panel(mut commands: Commands, panel_query: Query<Entity, With<HighScorePanel>>) {
+    for entity in panel_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(initials: &str, score: u32) -> HighScoreEntry {
+        HighScoreEntry {
+            initials: initials.to_string(),
+            score,
+            survival_seconds: 0.0,
+        }
+    }
+
+    fn full_table() -> HighScores {
+        let mut high_scores = HighScores::default();
+        for score in 1..=MAX_HIGH_SCORES as u32 {
+            high_scores.insert(entry("AAA", score * 10));
+        }
+        high_scores
+    }
+
+    #[test]
+    fn any_score_qualifies_until_the_table_is_full() {
+        let high_scores = HighScores::default();
+        assert!(!high_scores.qualifies(0));
+        assert!(high_scores.qualifies(1));
+    }
+
+    #[test]
+    fn a_full_table_needs_a_score_above_the_lowest() {
+        let high_scores = full_table();
+        assert!(!high_scores.qualifies(5));
+        assert!(!high_scores.qualifies(10));
+        assert!(high_scores.qualifies(11));
+    }
+
+    #[test]
+    fn insert_keeps_the_table_sorted_and_ties_in_order() {
+        let mut high_scores = HighScores::default();
+        high_scores.insert(entry("OLD", 20));
+        high_scores.insert(entry("LOW", 5));
+        high_scores.insert(entry("TOP", 50));
+        high_scores.insert(entry("NEW", 20));
+        let initials: Vec<&str> = high_scores.entries.iter().map(|entry| entry.initials.as_str()).collect();
+        assert_eq!(initials, ["TOP", "OLD", "NEW", "LOW"]);
+    }
+
+    #[test]
+    fn insert_drops_the_lowest_entry_from_a_full_table() {
+        let mut high_scores = full_table();
+        high_scores.insert(entry("NEW", 55));
+        assert_eq!(high_scores.entries.len(), MAX_HIGH_SCORES);
+        assert_eq!(high_scores.entries[0].score, 100);
+        assert_eq!(high_scores.entries[5].initials, "NEW");
+        assert_eq!(high_scores.entries.last().unwrap().score, 20);
+    }
+
+    #[test]
+    fn corrupt_file_is_moved_aside() {
+        let dir = std::env::temp_dir().join(format!("star_collector_highscores_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("highscores.ron");
+        fs::write(&path, "(entries: [(initials: ").unwrap();
+
+        assert!(read_high_scores(&path).entries.is_empty());
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(path.with_extension("ron.corrupt")).unwrap(), "(entries: [(initials: ");
+
+        // and the next save starts a fresh table in its place
+        write_high_scores(&path, &full_table());
+        assert_eq!(read_high_scores(&path).entries.len(), MAX_HIGH_SCORES);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
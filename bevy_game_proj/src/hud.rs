@@ -0,0 +1,257 @@
+use bevy::prelude::*;
+use game_common::score::Score;
+
+use crate::components::{Enemy, Lives, Player};
+use crate::events::PowerUpCollected;
+use crate::powerups::{ActivePowerUps, PowerUpKind};
+use crate::resources::SurvivalTime;
+use crate::states::AppState;
+use crate::config::GameConfig;
+use crate::waves::WaveStarted;
+
+pub const HUD_FONT_SIZE: f32 = 28.0;
+pub const WAVE_ANNOUNCEMENT_FONT_SIZE: f32 = 64.0;
+pub const WAVE_ANNOUNCEMENT_TIME: f32 = 2.0;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .add_systems(OnExit(AppState::MainMenu), spawn_hud)
+        .add_systems(OnEnter(AppState::MainMenu), despawn_hud)
+        .add_systems(Update, (update_score_text, update_survival_time_text, update_enemy_count_text, update_lives_text, (tint_power_up_text, update_power_up_text).chain())
+            .run_if(in_state(AppState::InGame)))
+        .add_systems(Update, (announce_waves, fade_wave_announcements).run_if(in_state(AppState::InGame)));
+    }
+}
+
+#[derive(Component)]
+pub struct Hud{}
+
+#[derive(Component)]
+pub struct ScoreText{}
+
+#[derive(Component)]
+pub struct SurvivalTimeText{}
+
+#[derive(Component)]
+pub struct EnemyCountText{}
+
+#[derive(Component)]
+pub struct LivesText{}
+
+#[derive(Component)]
+pub struct PowerUpText{}
+
+#[derive(Component)]
+pub struct WaveText{}
+
+// big "Wave N" banner, removed once the timer runs out
+#[derive(Component)]
+pub struct WaveAnnouncement{
+    pub timer: Timer,
+}
+
+fn hud_text(value: String) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size: HUD_FONT_SIZE,
+            color: Color::WHITE,
+            ..default()
+        },
+    )
+}
+
+pub fn spawn_hud(mut commands: Commands, config: Res<GameConfig>) {
+    commands.spawn(
+        (
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+            Hud {},
+        )
+    ).with_children(|parent| {
+        parent.spawn((hud_text("Score: 0".to_string()), ScoreText {}));
+        parent.spawn((hud_text("Time: 0.0s".to_string()), SurvivalTimeText {}));
+        parent.spawn((hud_text("Enemies: 0".to_string()), EnemyCountText {}));
+        parent.spawn((hud_text(format!("Lives: {}", config.player_lives)), LivesText {}));
+        parent.spawn((hud_text(String::new()), WaveText {}));
+        parent.spawn((hud_text(String::new()), PowerUpText {}));
+    });
+}
+
+pub fn despawn_hud(mut commands: Commands, hud_query: Query<Entity, Or<(With<Hud>, With<WaveAnnouncement>)>>) {
+    for entity in hud_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn update_score_text(
+    score: Res<Score>,
+    mut text_query: Query<&mut Text, With<ScoreText>>,
+) {
+    if score.is_changed() {
+        for mut text in text_query.iter_mut() {
+            text.sections[0].value = format!("Score: {}", score.value);
+        }
+    }
+}
+
+pub fn update_survival_time_text(
+    survival_time: Res<SurvivalTime>,
+    mut text_query: Query<&mut Text, With<SurvivalTimeText>>,
+) {
+    if survival_time.is_changed() {
+        for mut text in text_query.iter_mut() {
+            text.sections[0].value = format!("Time: {:.1}s", survival_time.seconds);
+        }
+    }
+}
+
+pub fn update_enemy_count_text(
+    enemy_query: Query<(), With<Enemy>>,
+    added_enemy_query: Query<(), Added<Enemy>>,
+    mut removed_enemies: RemovedComponents<Enemy>,
+    mut text_query: Query<&mut Text, With<EnemyCountText>>,
+) {
+    // only rewrite the text when an enemy was actually spawned or despawned
+    let removed = removed_enemies.iter().count() > 0;
+    if added_enemy_query.is_empty() && !removed {
+        return;
+    }
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format!("Enemies: {}", enemy_query.iter().count());
+    }
+}
+
+pub fn update_lives_text(
+    lives_query: Query<Ref<Lives>, With<Player>>,
+    mut removed_players: RemovedComponents<Player>,
+    mut text_query: Query<&mut Text, With<LivesText>>,
+) {
+    let removed = removed_players.iter().count() > 0;
+    let remaining = match lives_query.get_single() {
+        // a removal seen next to a live player was the previous run's, restarting replaces the player
+        Ok(lives) if lives.is_changed() || removed => lives.remaining,
+        Ok(_) => return,
+        // the player is despawned on the hit that takes the last life, so that change is never seen
+        Err(_) if removed => 0,
+        Err(_) => return,
+    };
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format!("Lives: {}", remaining);
+    }
+}
+
+pub fn update_power_up_text(
+    active_power_ups: Res<ActivePowerUps>,
+    mut text_query: Query<&mut Text, With<PowerUpText>>,
+) {
+    if !active_power_ups.is_changed() {
+        return;
+    }
+    let value = PowerUpKind::ALL
+        .iter()
+        .filter(|&&kind| active_power_ups.is_active(kind))
+        .map(|&kind| format!("{} {:.1}s", kind.name(), active_power_ups.remaining(kind)))
+        .collect::<Vec<String>>()
+        .join("  ");
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+// the power-up line takes the colour of the last one picked up
+pub fn tint_power_up_text(
+    mut collected: EventReader<PowerUpCollected>,
+    mut text_query: Query<&mut Text, With<PowerUpText>>,
+) {
+    let Some(power_up) = collected.iter().last() else {
+        return;
+    };
+    for mut text in text_query.iter_mut() {
+        text.sections[0].style.color = power_up.kind.color();
+    }
+}
+
+pub fn announce_waves(
+    mut commands: Commands,
+    mut wave_started: EventReader<WaveStarted>,
+    mut wave_text_query: Query<&mut Text, With<WaveText>>,
+    announcement_query: Query<Entity, With<WaveAnnouncement>>,
+) {
+    let Some(wave) = wave_started.iter().last() else {
+        return;
+    };
+    let label = if wave.endless {
+        format!("Wave {} (endless)", wave.number)
+    } else {
+        format!("Wave {}", wave.number)
+    };
+    for mut text in wave_text_query.iter_mut() {
+        text.sections[0].value = label.clone();
+    }
+
+    // a new banner replaces one that is still showing
+    for entity in announcement_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.spawn(
+        (
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(30.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            WaveAnnouncement {
+                timer: Timer::from_seconds(WAVE_ANNOUNCEMENT_TIME, TimerMode::Once),
+            },
+        )
+    ).with_children(|parent| {
+        parent.spawn(
+            TextBundle::from_section(
+                label,
+                TextStyle {
+                    font_size: WAVE_ANNOUNCEMENT_FONT_SIZE,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            )
+        );
+    });
+}
+
+pub fn fade_wave_announcements(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut announcement_query: Query<(Entity, &mut WaveAnnouncement, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (entity, mut announcement, children) in announcement_query.iter_mut() {
+        announcement.timer.tick(time.delta());
+        if announcement.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let alpha = 1.0 - announcement.timer.percent();
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].style.color.set_a(alpha);
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use std::time::Duration;
+
+use bevy::{input::InputPlugin, prelude::*, time::TimeUpdateStrategy};
+use game_common::{
+    arena::{confine_to_arena, ArenaBounds},
+    collision::detect_collisions,
+    movement::apply_velocity,
+    score::Score,
+    settings_menu::settings_menu_closed,
+    GameCommonPlugin,
+};
+
+pub mod actions;
+pub mod animation;
+pub mod audio;
+pub mod components;
+pub mod config;
+pub mod difficulty;
+pub mod director;
+pub mod enemies;
+pub mod events;
+pub mod highscores;
+pub mod hud;
+pub mod particles;
+pub mod pause_menu;
+pub mod pool;
+pub mod powerups;
+pub mod replay;
+pub mod resources;
+pub mod ron_asset;
+pub mod states;
+pub mod stats;
+pub mod systems;
+pub mod waves;
+
+use actions::ActionsPlugin;
+use audio::PlaySfx;
+use config::GameConfig;
+use difficulty::{update_difficulty_ramp, DifficultyPlugin};
+use director::tick_telegraphs;
+use enemies::{enemy_enemy_collision, steer_chasers, steer_wanderers, update_dashers};
+use events::{EnemyBounced, EnemySpawned, PlayerDied, PlayerHit, PowerUpCollected, StarCollected};
+use highscores::initials_entry_finished;
+use pool::EntityPool;
+use powerups::*;
+use replay::ReplayPlugin;
+use resources::*;
+use states::*;
+use stats::StatsPlugin;
+use systems::*;
+use waves::{update_waves, WavesPlugin};
+
+pub const PLAYER_SIZE: f32 = 64.0; // this is the players sprite size 
+pub const PLAYER_SPEED: f32 = 500.0; // players movement speed
+pub const NUMBER_OF_ENEMIES: usize = 4;
+pub const ENEMY_SPEED: f32 = 250.0;
+pub const ENEMY_SIZE: f32 = 64.0;
+pub const NUMBER_OF_STARS: usize = 10;
+pub const STAR_SIZE: f32 = 30.0;
+pub const STAR_SPAWN_TIME: f32 = 2.0;
+pub const ENEMY_SPAWN_TIME: f32 = 2.0;
+// set this to log the score to the console
+pub const DEBUG_MODE_ENV: &str = "STAR_COLLECTOR_DEBUG";
+pub const FIXED_TIMESTEP: f32 = 1.0 / 60.0;
+pub const PLAYER_LIVES: u32 = 3;
+pub const INVULNERABILITY_TIME: f32 = 2.0; // how long the player can't be hit again after losing a life
+pub const BLINK_INTERVAL: f32 = 0.1;
+pub const KNOCKBACK_SPEED: f32 = 900.0;
+pub const KNOCKBACK_DAMPING: f32 = 8.0;
+pub const MAX_ENEMIES: usize = 24;
+pub const MAX_STARS: usize = 20;
+pub const MAX_PARTICLES: usize = 600;
+pub const STAR_LIFETIME: f32 = 12.0; // uncollected stars disappear after this long
+pub const STAR_FADE_TIME: f32 = 2.0;
+// set this to use a settings file somewhere other than the platform config dir
+pub const SETTINGS_PATH_ENV: &str = "STAR_COLLECTOR_SETTINGS";
+
+// the fixed step is split so other plugins can hook in before or after the simulation proper
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SimulationSet {
+    Input,
+    Logic,
+}
+
+// the whole star collector simulation, without any window, rendering or ui plugins
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .add_state::<AppState>()
+        .add_plugins((GameCommonPlugin, ActionsPlugin, DifficultyPlugin, PowerUpPlugin, ReplayPlugin, StatsPlugin, WavesPlugin))
+        .init_resource::<Score>()
+        .init_resource::<SurvivalTime>()
+        .init_resource::<DebugMode>()
+        .init_resource::<StarSpawnTimer>()
+        .init_resource::<EnemySpawnTimer>()
+        .init_resource::<SimulationRng>()
+        .init_resource::<PlayerInput>()
+        .init_resource::<GameConfig>()
+        .init_resource::<EntityPool>()
+        .insert_resource(FixedTime::new_from_secs(FIXED_TIMESTEP))
+        .configure_set(FixedUpdate, SimulationSet::Input.run_if(in_state(AppState::InGame)))
+        .configure_set(FixedUpdate, SimulationSet::Logic.after(SimulationSet::Input).run_if(in_state(AppState::InGame)))
+        .add_systems(PreStartup, load_game_assets)
+        .add_event::<StarCollected>()
+        .add_event::<PlayerHit>()
+        .add_event::<PlayerDied>()
+        .add_event::<EnemyBounced>()
+        .add_event::<EnemySpawned>()
+        .add_event::<PowerUpCollected>()
+        .add_event::<RunAbandoned>()
+        // registered here too so the headless app, which has no sound plugin, can still run it
+        .add_event::<PlaySfx>()
+        .add_systems(OnEnter(AppState::MainMenu), (spawn_main_menu, despawn_run_entities))
+        .add_systems(RunStart, (reset_run_resources, spawn_player, spawn_enemy, spawn_stars).chain())
+        .add_systems(OnExit(AppState::MainMenu), (despawn_main_menu, start_new_run).chain())
+        .add_systems(OnEnter(AppState::Paused), freeze_time)
+        .add_systems(OnExit(AppState::Paused), unfreeze_time)
+        .add_systems(OnEnter(AppState::GameOver), spawn_game_over_screen)
+        .add_systems(OnExit(AppState::GameOver), (despawn_game_over_screen, despawn_run_entities, start_new_run).chain())
+        .add_systems(OnEnter(AppState::Restarting), (despawn_run_entities, finish_restart))
+        .add_systems(OnExit(AppState::Restarting), start_new_run)
+        // the simulation itself runs on a fixed step in a fixed order so that runs are reproducible
+        .add_systems(FixedUpdate, read_player_input.in_set(SimulationSet::Input))
+        .add_systems(FixedUpdate, (
+            (update_difficulty_ramp,
+                tick_star_spawn_timer,
+                tick_enemy_spawn_timer,
+                tick_power_up_spawn_timer,
+                spawn_enemy_over_time,
+                update_waves,
+                spawn_stars_over_time,
+                spawn_power_ups_over_time,
+                tick_active_power_ups,
+                tick_telegraphs).chain(),
+            (player_movement,
+                apply_knockback,
+                attract_stars,
+                steer_chasers,
+                steer_wanderers,
+                update_dashers,
+                enemy_movement,
+                apply_velocity,
+                update_enemy_direction,
+                confine_to_arena).chain(),
+            (detect_collisions,
+                enemy_enemy_collision,
+                tick_invulnerability,
+                player_collision,
+                end_run_on_player_death,
+                player_star_collision,
+                score_collected_stars,
+                player_power_up_collision,
+                tick_lifetimes,
+                tick_survival_time).chain(),
+        ).chain().in_set(SimulationSet::Logic))
+        .add_systems(Update, update_score.run_if(in_state(AppState::InGame).and_then(debug_enabled)))
+        .add_systems(Update, start_game.run_if(in_state(AppState::MainMenu).or_else(in_state(AppState::GameOver).and_then(initials_entry_finished))))
+        .add_systems(Update, toggle_pause.run_if(in_state(AppState::InGame).or_else(in_state(AppState::Paused)).and_then(settings_menu_closed)))
+        .add_systems(Update, exit_game.run_if(in_state(AppState::MainMenu).or_else(in_state(AppState::GameOver))));
+    }
+}
+
+// builds an app that runs without a window, gpu, audio device or asset folder.
+// every call to `app.update()` advances the simulation by exactly one FIXED_TIMESTEP tick
+pub fn headless_app(arena: ArenaBounds) -> App {
+    let mut app = App::new();
+    app
+    .add_plugins((MinimalPlugins, InputPlugin, GamePlugin))
+    .insert_resource(arena)
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(FIXED_TIMESTEP)));
+    app
+}
@@ -0,0 +1,249 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::config::GameConfig;
+use crate::events::{EnemyBounced, PlayerDied, StarCollected};
+use crate::states::AppState;
+
+pub const PARTICLE_Z: f32 = 1.0; // drawn over the sprites they come from
+
+// purely cosmetic: particles run on the frame clock and their own rng, so the simulation plays out
+// the same with or without this plugin. Effects are started by gameplay events or a SpawnEffect
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .add_event::<SpawnEffect>()
+        .add_systems(Update, (effects_for_gameplay_events, spawn_effect_emitters, emit_particles, update_particles)
+            .chain()
+            .run_if(not(in_state(AppState::Paused))));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EffectKind {
+    StarBurst,
+    WallSparks,
+    DeathExplosion,
+}
+
+impl EffectKind {
+    // particle count before GameConfig::particle_density is applied
+    pub fn count(&self) -> usize {
+        match self {
+            EffectKind::StarBurst => 16,
+            EffectKind::WallSparks => 6,
+            EffectKind::DeathExplosion => 80,
+        }
+    }
+
+    // particles per second, 0 emits everything on the first frame
+    pub fn rate(&self) -> f32 {
+        match self {
+            EffectKind::DeathExplosion => 400.0,
+            _ => 0.0,
+        }
+    }
+
+    pub fn color(&self, rng: &mut impl Rng) -> Color {
+        match self {
+            EffectKind::StarBurst => Color::rgb(1.0, 0.9, 0.3),
+            EffectKind::WallSparks => Color::rgb(1.0, 0.6, 0.2),
+            EffectKind::DeathExplosion => {
+                if rng.gen::<bool>() {
+                    Color::rgb(1.0, 0.3, 0.1)
+                } else {
+                    Color::rgb(1.0, 0.8, 0.2)
+                }
+            }
+        }
+    }
+
+    // (min, max) launch speed
+    pub fn speed(&self) -> (f32, f32) {
+        match self {
+            EffectKind::StarBurst => (80.0, 220.0),
+            EffectKind::WallSparks => (150.0, 350.0),
+            EffectKind::DeathExplosion => (100.0, 500.0),
+        }
+    }
+
+    // (min, max) seconds a particle lives
+    pub fn lifetime(&self) -> (f32, f32) {
+        match self {
+            EffectKind::StarBurst => (0.3, 0.6),
+            EffectKind::WallSparks => (0.15, 0.3),
+            EffectKind::DeathExplosion => (0.5, 1.2),
+        }
+    }
+
+    // (start, end) edge length of the square
+    pub fn size(&self) -> (f32, f32) {
+        match self {
+            EffectKind::StarBurst => (8.0, 2.0),
+            EffectKind::WallSparks => (4.0, 1.0),
+            EffectKind::DeathExplosion => (12.0, 3.0),
+        }
+    }
+
+    // how far either side of the effect's direction particles may fly, PI is a full circle
+    pub fn spread(&self) -> f32 {
+        match self {
+            EffectKind::WallSparks => PI / 3.0,
+            _ => PI,
+        }
+    }
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SpawnEffect {
+    pub kind: EffectKind,
+    pub position: Vec2,
+    // only matters for effects that don't spread all the way around
+    pub direction: Vec2,
+}
+
+#[derive(Component)]
+pub struct ParticleEmitter {
+    pub kind: EffectKind,
+    pub remaining: usize,
+    // fractional particles owed from earlier frames
+    pub carry: f32,
+    // angle the effect's direction points at
+    pub facing: f32,
+}
+
+#[derive(Component)]
+pub struct Particle {
+    pub velocity: Vec2,
+    pub lifetime: Timer,
+    pub drag: f32,
+    pub start_size: f32,
+    pub end_size: f32,
+}
+
+pub fn effects_for_gameplay_events(
+    mut collected: EventReader<StarCollected>,
+    mut bounced: EventReader<EnemyBounced>,
+    mut deaths: EventReader<PlayerDied>,
+    mut effects: EventWriter<SpawnEffect>,
+) {
+    effects.send_batch(collected.iter().map(|star| SpawnEffect {
+        kind: EffectKind::StarBurst,
+        position: star.position,
+        direction: Vec2::ZERO,
+    }));
+    effects.send_batch(bounced.iter().map(|bounce| SpawnEffect {
+        kind: EffectKind::WallSparks,
+        position: bounce.position,
+        direction: bounce.wall_normal,
+    }));
+    effects.send_batch(deaths.iter().map(|death| SpawnEffect {
+        kind: EffectKind::DeathExplosion,
+        position: death.position,
+        direction: Vec2::ZERO,
+    }));
+}
+
+pub fn spawn_effect_emitters(
+    mut commands: Commands,
+    mut effects: EventReader<SpawnEffect>,
+    config: Res<GameConfig>,
+) {
+    for effect in effects.iter() {
+        let remaining = (effect.kind.count() as f32 * config.particle_density).round() as usize;
+        if remaining == 0 {
+            continue;
+        }
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(effect.position.x, effect.position.y, PARTICLE_Z)),
+            ParticleEmitter {
+                kind: effect.kind,
+                remaining,
+                carry: 0.0,
+                facing: effect.direction.y.atan2(effect.direction.x),
+            },
+        ));
+    }
+}
+
+pub fn emit_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    particle_query: Query<(), With<Particle>>,
+    mut emitter_query: Query<(Entity, &Transform, &mut ParticleEmitter)>,
+) {
+    let mut rng = thread_rng();
+    let mut live = particle_query.iter().count();
+    for (entity, transform, mut emitter) in emitter_query.iter_mut() {
+        let kind = emitter.kind;
+        let wanted = if kind.rate() <= 0.0 {
+            emitter.remaining
+        } else {
+            emitter.carry += kind.rate() * time.delta_seconds();
+            let whole = emitter.carry.floor();
+            emitter.carry -= whole;
+            (whole as usize).min(emitter.remaining)
+        };
+        emitter.remaining -= wanted;
+        if emitter.remaining == 0 {
+            commands.entity(entity).despawn();
+        }
+
+        // over budget the extra particles are simply dropped
+        let count = wanted.min(config.max_particles.saturating_sub(live));
+        live += count;
+        let (min_speed, max_speed) = kind.speed();
+        let (min_lifetime, max_lifetime) = kind.lifetime();
+        let (start_size, end_size) = kind.size();
+        for _ in 0..count {
+            let angle = emitter.facing + rng.gen_range(-kind.spread()..=kind.spread());
+            let velocity = Vec2::from_angle(angle) * rng.gen_range(min_speed..=max_speed);
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: kind.color(&mut rng),
+                        custom_size: Some(Vec2::splat(start_size)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(transform.translation),
+                    ..default()
+                },
+                Particle {
+                    velocity,
+                    lifetime: Timer::from_seconds(rng.gen_range(min_lifetime..=max_lifetime), TimerMode::Once),
+                    drag: 3.0,
+                    start_size,
+                    end_size,
+                },
+            ));
+        }
+    }
+}
+
+pub fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particle_query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut particle, mut transform, mut sprite) in particle_query.iter_mut() {
+        particle.lifetime.tick(time.delta());
+        if particle.lifetime.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let drag = (1.0 - particle.drag * delta).max(0.0);
+        particle.velocity *= drag;
+        transform.translation += particle.velocity.extend(0.0) * delta;
+
+        let progress = particle.lifetime.percent();
+        let size = particle.start_size + (particle.end_size - particle.start_size) * progress;
+        sprite.custom_size = Some(Vec2::splat(size));
+        sprite.color.set_a(1.0 - progress);
+    }
+}
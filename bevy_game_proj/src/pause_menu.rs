@@ -0,0 +1,195 @@
+use bevy::{prelude::*, window::WindowFocused};
+use game_common::settings_menu::{settings_menu_closed, OpenSettings};
+
+use crate::actions::Action;
+use crate::states::{AppState, RunAbandoned};
+
+pub const PAUSE_MENU_FONT_SIZE: f32 = 40.0;
+pub const PAUSE_MENU_BACKDROP: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+pub const PAUSE_MENU_BUTTON: Color = Color::rgb(0.15, 0.15, 0.2);
+pub const PAUSE_MENU_BUTTON_SELECTED: Color = Color::rgb(0.35, 0.35, 0.55);
+
+// the overlay shown while a run is paused. The simulation already stands still outside of InGame,
+// this only draws the menu and turns its buttons into state changes
+pub struct PauseMenuPlugin;
+
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<PauseMenuSelection>()
+        // registered here too so the menu still runs without the settings screen
+        .add_event::<OpenSettings>()
+        .add_systems(OnEnter(AppState::Paused), spawn_pause_menu)
+        .add_systems(OnExit(AppState::Paused), despawn_pause_menu)
+        .add_systems(Update, (navigate_pause_menu, choose_pause_menu_item, highlight_pause_menu)
+            .chain()
+            .run_if(in_state(AppState::Paused).and_then(settings_menu_closed)))
+        .add_systems(Update, pause_on_focus_lost.run_if(in_state(AppState::InGame)));
+    }
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PauseMenuItem {
+    Resume,
+    Restart,
+    Settings,
+    QuitToMenu,
+}
+
+impl PauseMenuItem {
+    // top to bottom
+    pub const ALL: [PauseMenuItem; 4] = [PauseMenuItem::Resume, PauseMenuItem::Restart, PauseMenuItem::Settings, PauseMenuItem::QuitToMenu];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PauseMenuItem::Resume => "Resume",
+            PauseMenuItem::Restart => "Restart",
+            PauseMenuItem::Settings => "Settings",
+            PauseMenuItem::QuitToMenu => "Quit to menu",
+        }
+    }
+}
+
+// index into PauseMenuItem::ALL of the highlighted button
+#[derive(Resource, Default)]
+pub struct PauseMenuSelection(pub usize);
+
+#[derive(Component)]
+pub struct PauseMenu{}
+
+pub fn spawn_pause_menu(mut commands: Commands, mut selection: ResMut<PauseMenuSelection>) {
+    selection.0 = 0;
+    commands.spawn(
+        (
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(12.0),
+                    ..default()
+                },
+                background_color: PAUSE_MENU_BACKDROP.into(),
+                ..default()
+            },
+            PauseMenu {},
+        )
+    ).with_children(|parent| {
+        parent.spawn(
+            TextBundle::from_section(
+                "Paused",
+                TextStyle {
+                    font_size: 48.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            )
+        );
+        for item in PauseMenuItem::ALL {
+            parent.spawn(
+                (
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(320.0),
+                            padding: UiRect::all(Val::Px(8.0)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        background_color: PAUSE_MENU_BUTTON.into(),
+                        ..default()
+                    },
+                    item,
+                )
+            ).with_children(|button| {
+                button.spawn(
+                    TextBundle::from_section(
+                        item.label(),
+                        TextStyle {
+                            font_size: PAUSE_MENU_FONT_SIZE,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    )
+                );
+            });
+        }
+    });
+}
+
+pub fn despawn_pause_menu(mut commands: Commands, menu_query: Query<Entity, With<PauseMenu>>) {
+    for entity in menu_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// up/down on the keyboard or d-pad moves the highlight, wrapping around. Hovering a button with the mouse does too
+pub fn navigate_pause_menu(
+    actions: Res<Input<Action>>,
+    button_query: Query<(&Interaction, &PauseMenuItem), Changed<Interaction>>,
+    mut selection: ResMut<PauseMenuSelection>,
+) {
+    let count = PauseMenuItem::ALL.len();
+    if actions.just_pressed(Action::MoveUp) {
+        selection.0 = (selection.0 + count - 1) % count;
+    }
+    if actions.just_pressed(Action::MoveDown) {
+        selection.0 = (selection.0 + 1) % count;
+    }
+    for (interaction, item) in button_query.iter() {
+        if *interaction != Interaction::None {
+            selection.0 = PauseMenuItem::ALL.iter().position(|other| other == item).unwrap_or(0);
+        }
+    }
+}
+
+pub fn choose_pause_menu_item(
+    actions: Res<Input<Action>>,
+    button_query: Query<(&Interaction, &PauseMenuItem), Changed<Interaction>>,
+    selection: Res<PauseMenuSelection>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut open_settings: EventWriter<OpenSettings>,
+    mut run_abandoned: EventWriter<RunAbandoned>,
+) {
+    let clicked = button_query.iter().find(|(interaction, _)| **interaction == Interaction::Pressed).map(|(_, item)| *item);
+    let confirmed = actions.just_pressed(Action::Confirm).then_some(PauseMenuItem::ALL[selection.0]);
+    let Some(item) = clicked.or(confirmed) else {
+        return;
+    };
+    match item {
+        PauseMenuItem::Resume => next_state.set(AppState::InGame),
+        PauseMenuItem::Restart => {
+            run_abandoned.send(RunAbandoned);
+            next_state.set(AppState::Restarting);
+        }
+        PauseMenuItem::Settings => open_settings.send(OpenSettings),
+        PauseMenuItem::QuitToMenu => {
+            run_abandoned.send(RunAbandoned);
+            next_state.set(AppState::MainMenu);
+        }
+    }
+}
+
+pub fn highlight_pause_menu(
+    selection: Res<PauseMenuSelection>,
+    mut button_query: Query<(&PauseMenuItem, &mut BackgroundColor)>,
+) {
+    if !selection.is_changed() {
+        return;
+    }
+    let selected = PauseMenuItem::ALL[selection.0];
+    for (item, mut background) in button_query.iter_mut() {
+        *background = if *item == selected { PAUSE_MENU_BUTTON_SELECTED } else { PAUSE_MENU_BUTTON }.into();
+    }
+}
+
+// alt-tabbing away mid run shouldn't cost a life
+pub fn pause_on_focus_lost(
+    mut focus_events: EventReader<WindowFocused>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if focus_events.iter().any(|event| !event.focused) {
+        next_state.set(AppState::Paused);
+    }
+}
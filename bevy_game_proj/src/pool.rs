@@ -0,0 +1,128 @@
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use game_common::{arena::ConfineToArena, collision::Collider, movement::Velocity};
+
+use crate::animation::Animator;
+use crate::components::{Enemy, Lifetime, Star};
+use crate::director::Telegraph;
+use crate::enemies::{Chaser, Dasher, Wanderer};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PoolKind {
+    Enemy,
+    Star,
+}
+
+// a parked entity waiting to be reused: hidden and stripped of its gameplay components and animation
+#[derive(Component)]
+pub struct Pooled;
+
+// enemies and stars that leave play are parked here instead of despawned, so long sessions
+// recycle the same few entities rather than building fresh sprites every couple of seconds
+#[derive(Resource, Default)]
+pub struct EntityPool {
+    enemies: Vec<Entity>,
+    stars: Vec<Entity>,
+}
+
+impl EntityPool {
+    fn free_list(&mut self, kind: PoolKind) -> &mut Vec<Entity> {
+        match kind {
+            PoolKind::Enemy => &mut self.enemies,
+            PoolKind::Star => &mut self.stars,
+        }
+    }
+
+    // a parked entity of that kind if there is one, otherwise a new empty entity.
+    // the caller inserts the full bundle either way
+    pub fn acquire<'w, 's, 'a>(&mut self, commands: &'a mut Commands<'w, 's>, kind: PoolKind) -> EntityCommands<'w, 's, 'a> {
+        match self.free_list(kind).pop() {
+            Some(entity) => {
+                let mut reused = commands.entity(entity);
+                reused.remove::<Pooled>();
+                reused
+            }
+            None => commands.spawn_empty(),
+        }
+    }
+
+    pub fn release(&mut self, commands: &mut Commands, entity: Entity, kind: PoolKind) {
+        let free = self.free_list(kind);
+        // a star can be collected on the same tick its lifetime runs out
+        if free.contains(&entity) {
+            return;
+        }
+        free.push(entity);
+
+        let mut parked = commands.entity(entity);
+        parked.insert((Pooled, Visibility::Hidden));
+        match kind {
+            PoolKind::Enemy => {
+                parked.remove::<(Enemy, Collider, Velocity, ConfineToArena, Chaser, Wanderer, Dasher, Telegraph, Animator)>();
+            }
+            PoolKind::Star => {
+                parked.remove::<(Star, Collider, ConfineToArena, Lifetime, Animator)>();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::CommandQueue;
+
+    use super::*;
+    use crate::animation::STAR_SPIN;
+
+    // runs the commands queued by `f` against the world
+    fn with_commands<T>(world: &mut World, f: impl FnOnce(&mut Commands) -> T) -> T {
+        let mut queue = CommandQueue::default();
+        let result = f(&mut Commands::new(&mut queue, world));
+        queue.apply(world);
+        result
+    }
+
+    #[test]
+    fn released_entities_are_parked_and_handed_back_out() {
+        let mut world = World::new();
+        let mut pool = EntityPool::default();
+        let star = world.spawn((Star {}, Animator::new(STAR_SPIN), Visibility::Visible)).id();
+
+        with_commands(&mut world, |commands| pool.release(commands, star, PoolKind::Star));
+        assert!(world.get::<Star>(star).is_none());
+        // parked sprites shouldn't keep being animated
+        assert!(world.get::<Animator>(star).is_none());
+        assert!(world.get::<Pooled>(star).is_some());
+        assert_eq!(world.get::<Visibility>(star), Some(&Visibility::Hidden));
+
+        let reused = with_commands(&mut world, |commands| pool.acquire(commands, PoolKind::Star).id());
+        assert_eq!(reused, star);
+        assert!(world.get::<Pooled>(star).is_none());
+    }
+
+    #[test]
+    fn releasing_twice_parks_the_entity_once() {
+        let mut world = World::new();
+        let mut pool = EntityPool::default();
+        let star = world.spawn(Star {}).id();
+
+        with_commands(&mut world, |commands| {
+            pool.release(commands, star, PoolKind::Star);
+            pool.release(commands, star, PoolKind::Star);
+        });
+        let first = with_commands(&mut world, |commands| pool.acquire(commands, PoolKind::Star).id());
+        let second = with_commands(&mut world, |commands| pool.acquire(commands, PoolKind::Star).id());
+        assert_eq!(first, star);
+        assert_ne!(second, star);
+    }
+
+    #[test]
+    fn kinds_are_pooled_separately() {
+        let mut world = World::new();
+        let mut pool = EntityPool::default();
+        let star = world.spawn(Star {}).id();
+
+        with_commands(&mut world, |commands| pool.release(commands, star, PoolKind::Star));
+        let enemy = with_commands(&mut world, |commands| pool.acquire(commands, PoolKind::Enemy).id());
+        assert_ne!(enemy, star);
+    }
+}
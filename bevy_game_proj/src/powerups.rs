@@ -0,0 +1,320 @@
+use bevy::prelude::*;
+use game_common::{
+    arena::{ArenaBounds, ConfineToArena},
+    collision::{Collider, CollisionStarted},
+};
+use rand::prelude::*;
+
+use crate::components::{Player, Star, PICKUP_COLLISION};
+use crate::events::PowerUpCollected;
+use crate::resources::{GameAssets, SimulationRng};
+use crate::states::{reset_run_resources, RunStart};
+
+pub const POWER_UP_SIZE: f32 = 32.0;
+pub const POWER_UP_SPAWN_TIME: f32 = 8.0;
+pub const MAX_POWER_UPS_ON_FIELD: usize = 2;
+pub const MAX_POWER_UP_TIME: f32 = 20.0; // cap for power-ups whose durations add up
+pub const SHIELD_DURATION: f32 = 10.0;
+pub const SPEED_BOOST_DURATION: f32 = 5.0;
+pub const SPEED_BOOST_MULTIPLIER: f32 = 1.6;
+pub const STAR_MAGNET_DURATION: f32 = 8.0;
+pub const STAR_MAGNET_RADIUS: f32 = 250.0;
+pub const STAR_MAGNET_SPEED: f32 = 400.0;
+pub const SLOW_TIME_DURATION: f32 = 4.0;
+pub const SLOW_TIME_FACTOR: f32 = 0.4;
+
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<ActivePowerUps>()
+        .init_resource::<PowerUpSpawnTimer>()
+        .add_systems(RunStart, reset_power_ups.after(reset_run_resources));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PowerUpKind {
+    Shield,
+    SpeedBoost,
+    StarMagnet,
+    SlowTime,
+}
+
+// what happens when a power-up is picked up while the same kind is still running
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stacking {
+    // remaining time is topped back up to the full duration
+    Refresh,
+    // the new duration is added on top, up to MAX_POWER_UP_TIME
+    Extend,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 4] = [
+        PowerUpKind::Shield,
+        PowerUpKind::SpeedBoost,
+        PowerUpKind::StarMagnet,
+        PowerUpKind::SlowTime,
+    ];
+
+    pub fn duration(&self) -> f32 {
+        match self {
+            PowerUpKind::Shield => SHIELD_DURATION,
+            PowerUpKind::SpeedBoost => SPEED_BOOST_DURATION,
+            PowerUpKind::StarMagnet => STAR_MAGNET_DURATION,
+            PowerUpKind::SlowTime => SLOW_TIME_DURATION,
+        }
+    }
+
+    pub fn stacking(&self) -> Stacking {
+        match self {
+            PowerUpKind::Shield | PowerUpKind::SlowTime => Stacking::Refresh,
+            PowerUpKind::SpeedBoost | PowerUpKind::StarMagnet => Stacking::Extend,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerUpKind::Shield => "Shield",
+            PowerUpKind::SpeedBoost => "Speed",
+            PowerUpKind::StarMagnet => "Magnet",
+            PowerUpKind::SlowTime => "Slow",
+        }
+    }
+
+    // tint applied on top of the blue ball sprite
+    pub fn color(&self) -> Color {
+        match self {
+            PowerUpKind::Shield => Color::rgb(0.3, 1.0, 1.0),
+            PowerUpKind::SpeedBoost => Color::rgb(0.4, 1.0, 0.4),
+            PowerUpKind::StarMagnet => Color::rgb(1.0, 0.4, 1.0),
+            PowerUpKind::SlowTime => Color::rgb(1.0, 1.0, 1.0),
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            PowerUpKind::Shield => 0,
+            PowerUpKind::SpeedBoost => 1,
+            PowerUpKind::StarMagnet => 2,
+            PowerUpKind::SlowTime => 3,
+        }
+    }
+}
+
+// a pickup lying in the arena
+#[derive(Component)]
+pub struct PowerUp{
+    pub kind: PowerUpKind,
+}
+
+#[derive(Resource)]
+pub struct PowerUpSpawnTimer {
+    pub timer: Timer,
+}
+
+impl Default for PowerUpSpawnTimer {
+    fn default() -> PowerUpSpawnTimer {
+        PowerUpSpawnTimer {
+            timer: Timer::from_seconds(POWER_UP_SPAWN_TIME, TimerMode::Repeating),
+        }
+    }
+}
+
+// seconds left on each power-up, zero when it isn't running
+#[derive(Resource, Default)]
+pub struct ActivePowerUps {
+    remaining: [f32; 4],
+}
+
+impl ActivePowerUps {
+    pub fn remaining(&self, kind: PowerUpKind) -> f32 {
+        self.remaining[kind.index()]
+    }
+
+    pub fn is_active(&self, kind: PowerUpKind) -> bool {
+        self.remaining(kind) > 0.0
+    }
+
+    pub fn activate(&mut self, kind: PowerUpKind) {
+        let remaining = &mut self.remaining[kind.index()];
+        *remaining = match kind.stacking() {
+            Stacking::Refresh => remaining.max(kind.duration()),
+            Stacking::Extend => (*remaining + kind.duration()).min(MAX_POWER_UP_TIME),
+        };
+    }
+
+    // the shield soaks up exactly one hit, returns whether there was one to use
+    pub fn consume_shield(&mut self) -> bool {
+        if !self.is_active(PowerUpKind::Shield) {
+            return false;
+        }
+        self.remaining[PowerUpKind::Shield.index()] = 0.0;
+        true
+    }
+
+    pub fn tick(&mut self, delta: f32) {
+        for remaining in self.remaining.iter_mut() {
+            *remaining = (*remaining - delta).max(0.0);
+        }
+    }
+
+    pub fn player_speed_multiplier(&self) -> f32 {
+        if self.is_active(PowerUpKind::SpeedBoost) { SPEED_BOOST_MULTIPLIER } else { 1.0 }
+    }
+
+    pub fn enemy_time_scale(&self) -> f32 {
+        if self.is_active(PowerUpKind::SlowTime) { SLOW_TIME_FACTOR } else { 1.0 }
+    }
+}
+
+pub fn reset_power_ups(
+    mut active_power_ups: ResMut<ActivePowerUps>,
+    mut power_up_spawn_timer: ResMut<PowerUpSpawnTimer>,
+) {
+    *active_power_ups = ActivePowerUps::default();
+    power_up_spawn_timer.timer.reset();
+}
+
+pub fn tick_power_up_spawn_timer(
+    mut power_up_spawn_timer: ResMut<PowerUpSpawnTimer>,
+    fixed_time: Res<FixedTime>,
+) {
+    power_up_spawn_timer.timer.tick(fixed_time.period);
+}
+
+pub fn spawn_power_ups_over_time(
+    mut commands: Commands,
+    arena: Res<ArenaBounds>,
+    game_assets: Res<GameAssets>,
+    power_up_spawn_timer: Res<PowerUpSpawnTimer>,
+    power_up_query: Query<(), With<PowerUp>>,
+    mut rng: ResMut<SimulationRng>,
+) {
+    if !power_up_spawn_timer.timer.finished() || power_up_query.iter().count() >= MAX_POWER_UPS_ON_FIELD {
+        return;
+    }
+    let random_x: f32 = rng.gen::<f32>() * arena.width;
+    let random_y: f32 = rng.gen::<f32>() * arena.height;
+    let kind = PowerUpKind::ALL[rng.gen_range(0..PowerUpKind::ALL.len())];
+
+    commands.spawn((
+        SpriteBundle{
+            sprite: Sprite {
+                color: kind.color(),
+                custom_size: Some(Vec2::splat(POWER_UP_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_xyz(random_x, random_y, 0.0),
+            texture: game_assets.player_texture.clone(),
+            ..default()
+        },
+        PowerUp { kind },
+        ConfineToArena::square(POWER_UP_SIZE),
+        Collider::circle(POWER_UP_SIZE / 2.0, PICKUP_COLLISION),
+    ));
+}
+
+pub fn tick_active_power_ups(
+    mut active_power_ups: ResMut<ActivePowerUps>,
+    fixed_time: Res<FixedTime>,
+) {
+    // skip the write when nothing is running so change detection stays quiet
+    if PowerUpKind::ALL.iter().any(|&kind| active_power_ups.is_active(kind)) {
+        active_power_ups.tick(fixed_time.period.as_secs_f32());
+    }
+}
+
+pub fn attract_stars(
+    active_power_ups: Res<ActivePowerUps>,
+    player_query: Query<&Transform, With<Player>>,
+    mut star_query: Query<&mut Transform, (With<Star>, Without<Player>)>,
+    fixed_time: Res<FixedTime>,
+) {
+    if !active_power_ups.is_active(PowerUpKind::StarMagnet) {
+        return;
+    }
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let step = STAR_MAGNET_SPEED * fixed_time.period.as_secs_f32();
+    for mut star_transform in star_query.iter_mut() {
+        let to_player = player_transform.translation - star_transform.translation;
+        let distance = to_player.length();
+        if distance > 0.0 && distance < STAR_MAGNET_RADIUS {
+            star_transform.translation += to_player / distance * step.min(distance);
+        }
+    }
+}
+
+pub fn player_power_up_collision(
+    mut collisions: EventReader<CollisionStarted>,
+    power_up_query: Query<(Entity, &PowerUp, &Transform)>,
+    player_query: Query<Entity, With<Player>>,
+    mut commands: Commands,
+    mut collected: EventWriter<PowerUpCollected>,
+    mut active_power_ups: ResMut<ActivePowerUps>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    for collision in collisions.iter() {
+        let Some((entity, power_up, transform)) = collision.other(player).and_then(|other| power_up_query.get(other).ok()) else {
+            continue;
+        };
+        active_power_ups.activate(power_up.kind);
+        commands.entity(entity).despawn();
+        collected.send(PowerUpCollected {
+            kind: power_up.kind,
+            position: transform.translation.truncate(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refreshing_power_ups_top_back_up_to_their_duration() {
+        let mut active_power_ups = ActivePowerUps::default();
+        active_power_ups.activate(PowerUpKind::Shield);
+        active_power_ups.tick(3.0);
+        active_power_ups.activate(PowerUpKind::Shield);
+        assert_eq!(active_power_ups.remaining(PowerUpKind::Shield), SHIELD_DURATION);
+    }
+
+    #[test]
+    fn extending_power_ups_add_up_to_the_cap() {
+        let mut active_power_ups = ActivePowerUps::default();
+        active_power_ups.activate(PowerUpKind::SpeedBoost);
+        active_power_ups.activate(PowerUpKind::SpeedBoost);
+        assert_eq!(active_power_ups.remaining(PowerUpKind::SpeedBoost), SPEED_BOOST_DURATION * 2.0);
+        for _ in 0..10 {
+            active_power_ups.activate(PowerUpKind::SpeedBoost);
+        }
+        assert_eq!(active_power_ups.remaining(PowerUpKind::SpeedBoost), MAX_POWER_UP_TIME);
+    }
+
+    #[test]
+    fn power_ups_run_out_independently() {
+        let mut active_power_ups = ActivePowerUps::default();
+        active_power_ups.activate(PowerUpKind::SlowTime);
+        active_power_ups.activate(PowerUpKind::StarMagnet);
+        active_power_ups.tick(SLOW_TIME_DURATION);
+        assert!(!active_power_ups.is_active(PowerUpKind::SlowTime));
+        assert_eq!(active_power_ups.enemy_time_scale(), 1.0);
+        assert!(active_power_ups.is_active(PowerUpKind::StarMagnet));
+    }
+
+    #[test]
+    fn shield_soaks_up_a_single_hit() {
+        let mut active_power_ups = ActivePowerUps::default();
+        assert!(!active_power_ups.consume_shield());
+        active_power_ups.activate(PowerUpKind::Shield);
+        assert!(active_power_ups.consume_shield());
+        assert!(!active_power_ups.consume_shield());
+    }
+}
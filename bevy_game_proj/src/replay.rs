@@ -0,0 +1,286 @@
+use std::{fs, path::{Path, PathBuf}};
+
+use bevy::prelude::*;
+use game_common::{arena::{sync_arena_to_window, ArenaBounds, ArenaLocked}, score::Score};
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+use crate::difficulty::Difficulty;
+use crate::resources::{PlayerInput, SimulationRng};
+use crate::states::{reset_run_resources, AppState, RunAbandoned, RunStart};
+use crate::systems::read_player_input;
+use crate::waves::WaveScript;
+use crate::SimulationSet;
+
+// bumped whenever the simulation changes in a way that makes older recordings play out differently
+pub const REPLAY_VERSION: u32 = 15;
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<ReplayMode>()
+        .init_resource::<ReplayState>()
+        // before the camera is placed, so it is centred on the replay's arena
+        .add_systems(PreStartup, load_replay.after(sync_arena_to_window))
+        .add_systems(RunStart, begin_replay_run.after(reset_run_resources))
+        .add_systems(OnEnter(AppState::GameOver), finish_replay_run)
+        .add_systems(Update, save_abandoned_replay_run)
+        .add_systems(FixedUpdate, (playback_player_input, record_player_input)
+            .chain()
+            .after(read_player_input)
+            .in_set(SimulationSet::Input))
+        .add_systems(Update, start_playback.run_if(in_state(AppState::MainMenu).and_then(is_playing_back)));
+    }
+}
+
+// picked from the command line: `--record <file>` or `--replay <file>`
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub enum ReplayMode {
+    #[default]
+    Off,
+    Record(PathBuf),
+    Playback(PathBuf),
+}
+
+impl ReplayMode {
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> ReplayMode {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match (arg.as_str(), args.next()) {
+                ("--record", Some(path)) => return ReplayMode::Record(PathBuf::from(path)),
+                ("--replay", Some(path)) => return ReplayMode::Playback(PathBuf::from(path)),
+                _ => {}
+            }
+        }
+        ReplayMode::Off
+    }
+}
+
+// consecutive ticks with the same input are stored once, which keeps files small
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct InputRun {
+    pub ticks: u32,
+    pub input: PlayerInput,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    // where things spawn and bounce depends on the arena, so playback uses the recorded one
+    // rather than whatever size the window is
+    pub arena: ArenaBounds,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    // the tuning and waves the run was recorded with. Playback uses these instead of the
+    // asset files, which may have changed since or not have loaded yet
+    pub config: GameConfig,
+    pub wave_script: WaveScript,
+    pub ticks: u32,
+    pub final_score: Option<u32>,
+    pub inputs: Vec<InputRun>,
+}
+
+impl Replay {
+    pub fn push(&mut self, input: PlayerInput) {
+        self.ticks += 1;
+        match self.inputs.last_mut() {
+            Some(last) if last.input == input => last.ticks += 1,
+            _ => self.inputs.push(InputRun { ticks: 1, input }),
+        }
+    }
+
+    // input for the given tick, or no input once the recording has run out
+    pub fn input_at(&self, tick: u32) -> PlayerInput {
+        let mut start = 0;
+        for run in self.inputs.iter() {
+            if tick < start + run.ticks {
+                return run.input;
+            }
+            start += run.ticks;
+        }
+        PlayerInput::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayVerification {
+    Matched,
+    Mismatched { expected: u32, actual: u32 },
+}
+
+#[derive(Resource, Default)]
+pub struct ReplayState {
+    pub replay: Replay,
+    pub tick: u32,
+    pub verification: Option<ReplayVerification>,
+}
+
+pub fn is_playing_back(replay_mode: Res<ReplayMode>) -> bool {
+    matches!(*replay_mode, ReplayMode::Playback(_))
+}
+
+// true while a run is being recorded or played back, when the config and wave script it started
+// with have to stay put for the replay to come out the same
+pub fn run_is_recorded(replay_mode: &ReplayMode, state: &AppState) -> bool {
+    match replay_mode {
+        ReplayMode::Off => false,
+        ReplayMode::Record(_) => matches!(state, AppState::InGame | AppState::Paused),
+        ReplayMode::Playback(_) => true,
+    }
+}
+
+pub fn read_replay(path: &Path) -> Result<Replay, String> {
+    let contents = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let replay = ron::from_str::<Replay>(&contents).map_err(|err| err.to_string())?;
+    if replay.version != REPLAY_VERSION {
+        return Err(format!("unsupported replay version {}", replay.version));
+    }
+    Ok(replay)
+}
+
+pub fn write_replay(path: &Path, replay: &Replay) -> Result<(), String> {
+    let contents = ron::to_string(replay).map_err(|err| err.to_string())?;
+    fs::write(path, contents).map_err(|err| err.to_string())
+}
+
+// resizing the window mid run would change where things spawn and bounce, so the arena
+// stays the same size for the whole session while recording or playing back
+pub fn load_replay(
+    mut replay_mode: ResMut<ReplayMode>,
+    mut replay_state: ResMut<ReplayState>,
+    mut rng: ResMut<SimulationRng>,
+    mut arena: ResMut<ArenaBounds>,
+    mut arena_locked: ResMut<ArenaLocked>,
+    mut difficulty: ResMut<Difficulty>,
+    mut config: ResMut<GameConfig>,
+    mut wave_script: ResMut<WaveScript>,
+) {
+    let path = match replay_mode.clone() {
+        ReplayMode::Off => return,
+        ReplayMode::Record(_) => {
+            arena_locked.0 = true;
+            return;
+        }
+        ReplayMode::Playback(path) => path,
+    };
+    match read_replay(&path) {
+        Ok(replay) => {
+            info!("playing back {:?} ({} ticks, seed {})", path, replay.ticks, replay.seed);
+            rng.fixed_seed = Some(replay.seed);
+            *arena = replay.arena;
+            arena_locked.0 = true;
+            *difficulty = replay.difficulty;
+            *config = replay.config.clone();
+            *wave_script = replay.wave_script.clone();
+            replay_state.replay = replay;
+        }
+        Err(err) => {
+            error!("could not load replay {:?}: {}", path, err);
+            *replay_mode = ReplayMode::Off;
+        }
+    }
+}
+
+// nothing to wait for, the replay brought its own config and wave script
+pub fn start_playback(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::InGame);
+}
+
+pub fn begin_replay_run(
+    replay_mode: Res<ReplayMode>,
+    rng: Res<SimulationRng>,
+    arena: Res<ArenaBounds>,
+    difficulty: Res<Difficulty>,
+    config: Res<GameConfig>,
+    wave_script: Res<WaveScript>,
+    mut replay_state: ResMut<ReplayState>,
+) {
+    replay_state.tick = 0;
+    replay_state.verification = None;
+    if let ReplayMode::Record(_) = *replay_mode {
+        replay_state.replay = Replay {
+            version: REPLAY_VERSION,
+            seed: rng.seed,
+            arena: *arena,
+            difficulty: *difficulty,
+            config: config.clone(),
+            wave_script: wave_script.clone(),
+            ..default()
+        };
+    }
+}
+
+pub fn playback_player_input(
+    replay_mode: Res<ReplayMode>,
+    mut replay_state: ResMut<ReplayState>,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    if let ReplayMode::Playback(_) = *replay_mode {
+        *player_input = replay_state.replay.input_at(replay_state.tick);
+        replay_state.tick += 1;
+    }
+}
+
+pub fn record_player_input(
+    replay_mode: Res<ReplayMode>,
+    mut replay_state: ResMut<ReplayState>,
+    player_input: Res<PlayerInput>,
+) {
+    if let ReplayMode::Record(_) = *replay_mode {
+        replay_state.replay.push(*player_input);
+    }
+}
+
+pub fn finish_replay_run(
+    replay_mode: Res<ReplayMode>,
+    score: Res<Score>,
+    mut replay_state: ResMut<ReplayState>,
+) {
+    match &*replay_mode {
+        ReplayMode::Off => {}
+        ReplayMode::Record(path) => {
+            replay_state.replay.final_score = Some(score.value);
+            save_recording(path, &replay_state.replay);
+        }
+        ReplayMode::Playback(path) => {
+            let Some(expected) = replay_state.replay.final_score else {
+                warn!("replay {:?} has no final score to verify against", path);
+                return;
+            };
+            let verification = if expected == score.value {
+                info!("replay {:?} verified, final score {}", path, score.value);
+                ReplayVerification::Matched
+            } else {
+                error!("replay {:?} desynced: expected score {}, got {}", path, expected, score.value);
+                ReplayVerification::Mismatched { expected, actual: score.value }
+            };
+            replay_state.verification = Some(verification);
+        }
+    }
+}
+
+// a run left from the pause menu is still written out, read before the next run starts recording
+// over it. It has no final score, so playing it back just skips the check at the end
+pub fn save_abandoned_replay_run(
+    mut run_abandoned: EventReader<RunAbandoned>,
+    replay_mode: Res<ReplayMode>,
+    mut replay_state: ResMut<ReplayState>,
+) {
+    if run_abandoned.iter().count() == 0 {
+        return;
+    }
+    if let ReplayMode::Record(path) = &*replay_mode {
+        replay_state.replay.final_score = None;
+        save_recording(path, &replay_state.replay);
+    }
+}
+
+fn save_recording(path: &Path, replay: &Replay) {
+    match write_replay(path, replay) {
+        Ok(()) => info!("saved replay to {:?}", path),
+        Err(err) => error!("could not save replay {:?}: {}", path, err),
+    }
+}
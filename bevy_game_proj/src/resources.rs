@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{DEBUG_MODE_ENV, ENEMY_SPAWN_TIME, STAR_SPAWN_TIME};
+
+#[derive(Resource)]
+pub struct SurvivalTime {
+    pub seconds: f32,
+}
+
+impl Default for SurvivalTime {
+    fn default() -> SurvivalTime {
+        SurvivalTime {
+            seconds: 0.0
+        }
+    }
+}
+
+// console logging is off unless the game is started with DEBUG_MODE_ENV set
+#[derive(Resource)]
+pub struct DebugMode {
+    pub enabled: bool,
+}
+
+impl Default for DebugMode {
+    fn default() -> DebugMode {
+        DebugMode {
+            enabled: std::env::var_os(DEBUG_MODE_ENV).is_some(),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct StarSpawnTimer {
+    pub timer: Timer,
+}
+
+impl Default for StarSpawnTimer {
+    fn default() -> StarSpawnTimer {
+        StarSpawnTimer {
+            timer: Timer::from_seconds(STAR_SPAWN_TIME, TimerMode::Repeating), 
+        }
+    }
+}
+#[derive(Resource)]
+pub struct EnemySpawnTimer {
+    pub timer: Timer,
+}
+impl Default for EnemySpawnTimer {
+    fn default() -> EnemySpawnTimer {
+        EnemySpawnTimer {
+            timer: Timer::from_seconds(ENEMY_SPAWN_TIME, TimerMode::Repeating),
+        }
+    }
+}
+
+// every texture the game uses. Left as default handles when there is no AssetServer,
+// so the simulation runs the same without any assets on disk. Sounds live in audio::SfxHandles
+#[derive(Resource, Default)]
+pub struct GameAssets {
+    pub player_texture: Handle<Image>,
+    pub player_atlas: Handle<TextureAtlas>,
+    pub enemy_atlas: Handle<TextureAtlas>,
+    pub star_atlas: Handle<TextureAtlas>,
+}
+
+// the movement the player asked for this tick. Quantized to whole steps of 1/127 per axis
+// so a recorded run feeds exactly the same numbers back into the simulation
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct PlayerInput {
+    pub x: i8,
+    pub y: i8,
+}
+
+impl PlayerInput {
+    pub fn from_direction(direction: Vec2) -> PlayerInput {
+        let direction = direction.clamp_length_max(1.0);
+        PlayerInput {
+            x: (direction.x * 127.0).round() as i8,
+            y: (direction.y * 127.0).round() as i8,
+        }
+    }
+
+    pub fn direction(&self) -> Vec2 {
+        (Vec2::new(self.x as f32, self.y as f32) / 127.0).clamp_length_max(1.0)
+    }
+}
+
+// set this to replay the same seed every run instead of picking a fresh one
+pub const SEED_ENV: &str = "STAR_COLLECTOR_SEED";
+
+// the only source of randomness the simulation is allowed to use. Reseeded at the start of every run,
+// so the same seed and the same input always play out the same way
+#[derive(Resource)]
+pub struct SimulationRng {
+    pub seed: u64,
+    pub fixed_seed: Option<u64>,
+    // a fixed algorithm rather than StdRng, whose output rand may change between versions or platforms.
+    // the same seed has to keep playing out the same run
+    pub generator: ChaCha8Rng,
+}
+
+impl SimulationRng {
+    pub fn new(fixed_seed: Option<u64>) -> SimulationRng {
+        let seed = fixed_seed.unwrap_or_else(random::<u64>);
+        SimulationRng {
+            seed,
+            fixed_seed,
+            generator: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn start_run(&mut self) {
+        self.seed = self.fixed_seed.unwrap_or_else(random::<u64>);
+        self.generator = ChaCha8Rng::seed_from_u64(self.seed);
+    }
+}
+
+impl Default for SimulationRng {
+    fn default() -> SimulationRng {
+        let fixed_seed = std::env::var(SEED_ENV).ok().and_then(|seed| seed.parse::<u64>().ok());
+        SimulationRng::new(fixed_seed)
+    }
+}
+
+impl RngCore for SimulationRng {
+    fn next_u32(&mut self) -> u32 {
+        self.generator.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.generator.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.generator.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.generator.try_fill_bytes(dest)
+    }
+}
@@ -0,0 +1,130 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{Asset, AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::de::DeserializeOwned;
+
+use crate::replay::{run_is_recorded, ReplayMode};
+use crate::states::AppState;
+
+// a RON file in the assets folder that is checked before the game gets to use it
+pub trait RonAsset: Asset + Resource + DeserializeOwned + Clone {
+    // what the file is called in log messages, e.g. "game config"
+    const NAME: &'static str;
+    const EXTENSIONS: &'static [&'static str];
+
+    fn validate(&self) -> Result<(), String>;
+}
+
+// parses and validates the file. A rejected file leaves whatever was loaded before in place
+pub struct RonAssetLoader<A> {
+    marker: PhantomData<fn() -> A>,
+}
+
+impl<A> Default for RonAssetLoader<A> {
+    fn default() -> RonAssetLoader<A> {
+        RonAssetLoader { marker: PhantomData }
+    }
+}
+
+impl<A: RonAsset> AssetLoader for RonAssetLoader<A> {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let asset: A = ron::de::from_bytes(bytes)?;
+            asset.validate().map_err(|error| {
+                bevy::asset::Error::msg(format!("invalid {} {}: {}", A::NAME, load_context.path().display(), error))
+            })?;
+            load_context.set_default_asset(LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        A::EXTENSIONS
+    }
+}
+
+// loads `path` into the `A` resource once it is ready and again after every edit.
+// without this plugin (e.g. headless) the resource keeps its built in default
+pub struct RonAssetPlugin<A> {
+    pub path: &'static str,
+    // a run that is being recorded or played back keeps the file it started with, so an edit
+    // made meanwhile waits for the run to end
+    pub held_during_replays: bool,
+    marker: PhantomData<fn() -> A>,
+}
+
+impl<A> RonAssetPlugin<A> {
+    pub fn new(path: &'static str) -> RonAssetPlugin<A> {
+        RonAssetPlugin {
+            path,
+            held_during_replays: false,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn held_during_replays(mut self) -> RonAssetPlugin<A> {
+        self.held_during_replays = true;
+        self
+    }
+}
+
+impl<A: RonAsset> Plugin for RonAssetPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app
+        .add_asset::<A>()
+        .init_asset_loader::<RonAssetLoader<A>>()
+        .insert_resource(RonAssetFile::<A> {
+            path: self.path,
+            held_during_replays: self.held_during_replays,
+            handle: Handle::default(),
+            pending: false,
+        })
+        .add_systems(PreStartup, load_ron_asset::<A>)
+        .add_systems(Update, apply_ron_asset::<A>);
+    }
+}
+
+#[derive(Resource)]
+pub struct RonAssetFile<A: RonAsset> {
+    pub path: &'static str,
+    pub held_during_replays: bool,
+    pub handle: Handle<A>,
+    // the file loaded or changed but hasn't been copied into the resource yet
+    pub pending: bool,
+}
+
+pub fn load_ron_asset<A: RonAsset>(mut file: ResMut<RonAssetFile<A>>, asset_server: Res<AssetServer>) {
+    file.handle = asset_server.load(file.path);
+}
+
+pub fn apply_ron_asset<A: RonAsset>(
+    mut asset_events: EventReader<AssetEvent<A>>,
+    mut file: ResMut<RonAssetFile<A>>,
+    assets: Res<Assets<A>>,
+    replay_mode: Res<ReplayMode>,
+    state: Res<State<AppState>>,
+    mut resource: ResMut<A>,
+) {
+    for event in asset_events.iter() {
+        if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = event {
+            file.pending |= *handle == file.handle;
+        }
+    }
+    if !file.pending || (file.held_during_replays && run_is_recorded(&replay_mode, state.get())) {
+        return;
+    }
+    let Some(loaded) = assets.get(&file.handle) else {
+        return;
+    };
+    file.pending = false;
+    *resource = loaded.clone();
+    info!("loaded {} from {}", A::NAME, file.path);
+}
@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+use game_common::{collision::Contacts, score::Score};
+
+use crate::actions::Action;
+use crate::config::GameConfig;
+use crate::components::*;
+use crate::pool::{EntityPool, PoolKind};
+use crate::powerups::PowerUp;
+use crate::resources::*;
+use crate::stats::RunStats;
+
+#[derive(States, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum AppState {
+    #[default]
+    MainMenu,
+    InGame,
+    Paused,
+    // passed through for a frame on the way from the pause menu back into a fresh run
+    Restarting,
+    GameOver,
+}
+
+// sets up a fresh run. Leaving the main menu, leaving the game over screen and restarting all run it,
+// so anything that has to be reset for each run is added here once, after reset_run_resources
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RunStart;
+
+pub fn start_new_run(world: &mut World) {
+    world.run_schedule(RunStart);
+}
+
+#[derive(Component)]
+pub struct MainMenuScreen{}
+
+#[derive(Component)]
+pub struct GameOverScreen{}
+
+pub fn toggle_pause(
+    actions: Res<Input<Action>>,
+    app_state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    // backing out with Quit pauses a run rather than closing the game
+    if actions.just_pressed(Action::Pause) || actions.just_pressed(Action::Quit) {
+        match app_state.get() {
+            AppState::InGame => next_state.set(AppState::Paused),
+            AppState::Paused => next_state.set(AppState::InGame),
+            _ => {}
+        }
+    }
+}
+
+// the clock stands still while paused, so timers and anything else reading Time pick up where they left off
+pub fn freeze_time(mut time: ResMut<Time>) {
+    time.pause();
+}
+
+pub fn unfreeze_time(mut time: ResMut<Time>) {
+    time.unpause();
+}
+
+// the run was left from the pause menu, by restarting or quitting to the main menu,
+// so it never reaches game over
+#[derive(Event, Clone, Copy, Debug)]
+pub struct RunAbandoned;
+
+// the old run is cleared on the way in and the new one set up on the way out, the same as
+// leaving the game over screen, so there is nothing left to do here but carry on
+pub fn finish_restart(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::InGame);
+}
+
+pub fn start_game(
+    actions: Res<Input<Action>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if actions.just_pressed(Action::Confirm) {
+        next_state.set(AppState::InGame);
+    }
+}
+
+// clears everything left over from the previous run so the next one starts fresh
+pub fn despawn_run_entities(
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool>,
+    entity_query: Query<Entity, Or<(With<Player>, With<PowerUp>)>>,
+    enemy_query: Query<Entity, With<Enemy>>,
+    star_query: Query<Entity, With<Star>>,
+) {
+    for entity in entity_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    // enemies and stars are parked for the next run instead
+    for entity in enemy_query.iter() {
+        pool.release(&mut commands, entity, PoolKind::Enemy);
+    }
+    for entity in star_query.iter() {
+        pool.release(&mut commands, entity, PoolKind::Star);
+    }
+}
+
+pub fn reset_run_resources(
+    mut score: ResMut<Score>,
+    mut survival_time: ResMut<SurvivalTime>,
+    mut star_spawn_timer: ResMut<StarSpawnTimer>,
+    mut enemy_spawn_timer: ResMut<EnemySpawnTimer>,
+    mut rng: ResMut<SimulationRng>,
+    mut contacts: ResMut<Contacts>,
+    config: Res<GameConfig>,
+) {
+    rng.start_run();
+    // the previous run's entities are gone, their contacts shouldn't end up as CollisionEnded events
+    contacts.clear();
+    info!("starting run with seed {}", rng.seed);
+    score.value = 0;
+    survival_time.seconds = 0.0;
+    star_spawn_timer.timer.set_duration(Duration::from_secs_f32(config.star_spawn_time));
+    star_spawn_timer.timer.reset();
+    enemy_spawn_timer.timer.reset();
+}
+
+//menu screens
+
+fn spawn_screen_text(commands: &mut Commands, text: String, marker: impl Component) {
+    commands.spawn(
+        (
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            marker,
+        )
+    ).with_children(|parent| {
+        parent.spawn(
+            TextBundle::from_section(
+                text,
+                TextStyle {
+                    font_size: 48.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ).with_text_alignment(TextAlignment::Center)
+        );
+    });
+}
+
+pub fn spawn_main_menu(mut commands: Commands) {
+    spawn_screen_text(&mut commands, "Star Collector\nPress Space or (A) to play\nEsc or (Select) to quit".to_string(), MainMenuScreen {});
+}
+
+pub fn despawn_main_menu(mut commands: Commands, screen_query: Query<Entity, With<MainMenuScreen>>) {
+    for entity in screen_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn spawn_game_over_screen(mut commands: Commands, score: Res<Score>, stats: Res<RunStats>) {
+    spawn_screen_text(
+        &mut commands,
+        format!(
+            "Game Over\nScore: {}\nHits taken: {}  Shielded: {}  Enemies faced: {}  Power-ups: {}\nPress Space or (A) to play again",
+            score.value, stats.hits_taken, stats.hits_shielded, stats.enemies_spawned, stats.power_ups_collected,
+        ),
+        GameOverScreen {},
+    );
+}
+
+pub fn despawn_game_over_screen(mut commands: Commands, screen_query: Query<Entity, With<GameOverScreen>>) {
+    for entity in screen_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
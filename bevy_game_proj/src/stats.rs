@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+use crate::events::{EnemyBounced, EnemySpawned, PlayerHit, PowerUpCollected, StarCollected};
+use crate::states::{reset_run_resources, RunStart};
+
+// tallies for the current run, shown on the game over screen. Only ever fed by gameplay events
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<RunStats>()
+        .add_systems(RunStart, reset_run_stats.after(reset_run_resources))
+        .add_systems(Update, record_run_stats);
+    }
+}
+
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct RunStats {
+    pub stars_collected: u32,
+    pub hits_taken: u32,
+    pub hits_shielded: u32,
+    pub enemies_spawned: u32,
+    pub enemy_bounces: u32,
+    pub power_ups_collected: u32,
+}
+
+pub fn reset_run_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+pub fn record_run_stats(
+    mut collected: EventReader<StarCollected>,
+    mut hits: EventReader<PlayerHit>,
+    mut spawned: EventReader<EnemySpawned>,
+    mut bounced: EventReader<EnemyBounced>,
+    mut power_ups: EventReader<PowerUpCollected>,
+    mut stats: ResMut<RunStats>,
+) {
+    stats.stars_collected += collected.iter().count() as u32;
+    for hit in hits.iter() {
+        if hit.shielded {
+            stats.hits_shielded += 1;
+        } else {
+            stats.hits_taken += 1;
+        }
+    }
+    stats.enemies_spawned += spawned.iter().count() as u32;
+    stats.enemy_bounces += bounced.iter().count() as u32;
+    stats.power_ups_collected += power_ups.iter().count() as u32;
+}
@@ -0,0 +1,451 @@
+use bevy::{prelude::*, app::AppExit};
+use game_common::{
+    arena::{ArenaBounds, ConfineToArena},
+    collision::{Collider, CollisionStarted, Contacts},
+    movement::Velocity,
+    score::Score,
+};
+use rand::prelude::*;
+
+use crate::actions::{Action, MoveAxis};
+use crate::animation::{
+    sheet_atlas, Animator, ENEMY_SHEET, ENEMY_SHEET_GRID, PLAYER_IDLE, PLAYER_SHEET, PLAYER_SHEET_GRID, STAR_SHEET, STAR_SHEET_GRID,
+    STAR_SPIN,
+};
+use crate::components::*;
+use crate::config::GameConfig;
+use crate::difficulty::{Difficulty, DifficultyRamp};
+use crate::director::{plan_spawns, SpawnPattern, Telegraph};
+use crate::enemies::{spawn_enemy_of_kind, EnemyKind, Wanderer};
+use crate::events::{EnemyBounced, EnemySpawned, PlayerDied, PlayerHit, StarCollected};
+use crate::pool::{EntityPool, PoolKind};
+use crate::powerups::ActivePowerUps;
+use crate::resources::*;
+use crate::states::AppState;
+use crate::{
+    BLINK_INTERVAL, ENEMY_SIZE, PLAYER_SIZE, STAR_SIZE,
+};
+
+pub fn load_game_assets(
+    mut commands: Commands,
+    asset_server: Option<Res<AssetServer>>,
+    atlases: Option<ResMut<Assets<TextureAtlas>>>,
+) {
+    let (Some(asset_server), Some(mut atlases)) = (asset_server, atlases) else {
+        commands.init_resource::<GameAssets>();
+        return;
+    };
+    commands.insert_resource(GameAssets {
+        player_texture: asset_server.load("sprites/ball_blue_large.png"),
+        player_atlas: sheet_atlas(&asset_server, &mut atlases, PLAYER_SHEET, PLAYER_SIZE, PLAYER_SHEET_GRID),
+        enemy_atlas: sheet_atlas(&asset_server, &mut atlases, ENEMY_SHEET, ENEMY_SIZE, ENEMY_SHEET_GRID),
+        star_atlas: sheet_atlas(&asset_server, &mut atlases, STAR_SHEET, STAR_SIZE, STAR_SHEET_GRID),
+    });
+}
+
+pub fn debug_enabled(debug_mode: Res<DebugMode>) -> bool {
+    debug_mode.enabled
+}
+
+pub fn spawn_player(
+    mut commands: Commands,
+    arena: Res<ArenaBounds>,
+    game_assets: Res<GameAssets>,
+    config: Res<GameConfig>,
+){
+    commands.spawn(
+        (
+            SpriteSheetBundle{
+                transform: Transform:: from_xyz(arena.width / 2.0, arena.height / 2.0, 0.0),
+                texture_atlas: game_assets.player_atlas.clone(),
+                ..default()
+            },
+            Animator::new(PLAYER_IDLE),
+            Player {},
+            Velocity::default(),
+            ConfineToArena::square(PLAYER_SIZE),
+            Collider::circle(PLAYER_SIZE / 2.0, PLAYER_COLLISION),
+            Lives {
+                remaining: config.player_lives,
+            },
+        )
+    );
+}
+
+// the player is spawned in the same frame, so until then assume they'll be in the middle
+pub fn player_position(player_query: &Query<&Transform, With<Player>>, arena: &ArenaBounds) -> Vec2 {
+    player_query
+        .get_single()
+        .map(|transform| transform.translation.truncate())
+        .unwrap_or_else(|_| arena.center())
+}
+
+pub fn spawn_enemy(
+    mut commands: Commands,
+    arena: Res<ArenaBounds>,
+    game_assets: Res<GameAssets>,
+    difficulty: Res<Difficulty>,
+    config: Res<GameConfig>,
+    player_query: Query<&Transform, With<Player>>,
+    mut pool: ResMut<EntityPool>,
+    mut rng: ResMut<SimulationRng>,
+    mut enemy_spawned: EventWriter<EnemySpawned>,
+){
+    let count = difficulty.settings().starting_enemies.min(config.max_enemies);
+    let player_position = player_position(&player_query, &arena);
+    for point in plan_spawns(SpawnPattern::Scatter, count, &arena, player_position, &mut *rng) {
+        let kind = EnemyKind::random(&mut *rng);
+        spawn_enemy_of_kind(&mut commands, &mut pool, &game_assets, kind, point.position, point.direction, &mut enemy_spawned);
+    }
+}
+
+pub fn spawn_stars(
+    mut commands: Commands,
+    arena: Res<ArenaBounds>,
+    game_assets: Res<GameAssets>,
+    config: Res<GameConfig>,
+    mut pool: ResMut<EntityPool>,
+    mut rng: ResMut<SimulationRng>,
+){
+    for _ in 0..config.number_of_stars.min(config.max_stars) {
+        let random_x: f32 = rng.gen::<f32>() * arena.width;
+        let random_y: f32 = rng.gen::<f32>() * arena.height;
+
+        spawn_star(&mut commands, &mut pool, &game_assets, &config, Vec2::new(random_x, random_y));
+    }
+}
+
+pub fn spawn_star(
+    commands: &mut Commands,
+    pool: &mut EntityPool,
+    game_assets: &GameAssets,
+    config: &GameConfig,
+    position: Vec2,
+) {
+    pool.acquire(commands, PoolKind::Star).insert((
+        SpriteSheetBundle{
+            transform: Transform::from_xyz(position.x, position.y, 0.0),
+            texture_atlas: game_assets.star_atlas.clone(),
+            ..default()
+        },
+        Animator::new(STAR_SPIN),
+        Star {},
+        ConfineToArena::square(STAR_SIZE),
+        Collider::circle(STAR_SIZE / 2.0, PICKUP_COLLISION),
+        Lifetime {
+            timer: Timer::from_seconds(config.star_lifetime, TimerMode::Once),
+            fade_out: config.star_fade_time,
+        },
+    ));
+}
+
+
+
+
+pub fn read_player_input(
+    move_axis: Res<MoveAxis>,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    *player_input = PlayerInput::from_direction(move_axis.value);
+}
+
+pub fn player_movement(
+    player_input: Res<PlayerInput>,
+    mut player_query: Query<&mut Velocity, With<Player>>,
+    active_power_ups: Res<ActivePowerUps>,
+    config: Res<GameConfig>,
+) {
+    if let Ok(mut velocity) = player_query.get_single_mut() {
+        let speed = config.player_speed * active_power_ups.player_speed_multiplier();
+        velocity.0 = player_input.direction() * speed;
+    }
+}
+
+// telegraphing enemies hold still until the warning is over
+pub fn enemy_movement(
+    mut enemy_query: Query<(&mut Velocity, &Enemy, Option<&Telegraph>)>,
+    difficulty_ramp: Res<DifficultyRamp>,
+    active_power_ups: Res<ActivePowerUps>,
+    config: Res<GameConfig>,
+){
+    for (mut velocity, enemy, telegraph) in enemy_query.iter_mut(){
+        if telegraph.is_some() {
+            velocity.0 = Vec2::ZERO;
+            continue;
+        }
+        let speed = enemy.speed
+            * config.enemy_speed_scale()
+            * difficulty_ramp.enemy_speed_multiplier
+            * active_power_ups.enemy_time_scale();
+        velocity.0 = enemy.direction * speed;
+    }
+}
+
+pub fn update_enemy_direction(
+    mut enemy_query: Query<(Entity, &Transform, &mut Enemy, Option<&mut Wanderer>)>,
+    arena: Res<ArenaBounds>,
+    mut bounced: EventWriter<EnemyBounced>,
+){
+    for(entity, transform, mut enemy, mut wanderer) in enemy_query.iter_mut(){
+        let half_enemy_size: f32 = enemy.kind.size() / 2.0;
+        let x_min: f32 = 0.0 + half_enemy_size;
+        let x_max: f32 = arena.width - half_enemy_size;
+        let y_min: f32 = 0.0 + half_enemy_size;
+        let y_max: f32 = arena.height - half_enemy_size;
+
+        let mut direction_changed = false;
+        // points away from the wall(s) that were hit
+        let mut wall_normal = Vec2::ZERO;
+
+        let translation: Vec3 = transform.translation;
+        if translation.x < x_min || translation.x > x_max{
+            wall_normal.x = if translation.x < x_min { 1.0 } else { -1.0 };
+            enemy.direction.x *= -1.0;
+            // otherwise a wanderer steers straight back into the wall it just hit
+            if let Some(wanderer) = wanderer.as_mut() {
+                wanderer.target_direction.x *= -1.0;
+            }
+            direction_changed = true;
+        }
+        if translation.y < y_min || translation.y > y_max{
+            wall_normal.y = if translation.y < y_min { 1.0 } else { -1.0 };
+            enemy.direction.y *= -1.0;
+            if let Some(wanderer) = wanderer.as_mut() {
+                wanderer.target_direction.y *= -1.0;
+            }
+            direction_changed = true;
+        }
+        if direction_changed{
+            let wall_normal = wall_normal.normalize();
+            bounced.send(EnemyBounced {
+                enemy: entity,
+                position: translation.truncate() - wall_normal * half_enemy_size,
+                wall_normal,
+            });
+        }
+    }
+}
+
+// reads Contacts rather than CollisionStarted so an enemy still touching the player when
+// invulnerability runs out gets its hit in
+pub fn player_collision(
+    contacts: Res<Contacts>,
+    enemy_query: Query<&Transform, With<Enemy>>,
+    mut player_query: Query<(Entity, &Transform, &mut Lives), (With<Player>, Without<Invulnerable>)>,
+    mut commands: Commands,
+    mut hits: EventWriter<PlayerHit>,
+    mut deaths: EventWriter<PlayerDied>,
+    mut active_power_ups: ResMut<ActivePowerUps>,
+    config: Res<GameConfig>,
+){
+    if let Ok((player_entity, player_transform, mut lives)) = player_query.get_single_mut() {
+        let player_position = player_transform.translation.truncate();
+        // one hit per tick, even when touching several enemies at once
+        if let Some(enemy_transform) = contacts.touching(player_entity).find_map(|other| enemy_query.get(other).ok()) {
+            let shielded = active_power_ups.consume_shield();
+            if !shielded {
+                lives.remaining = lives.remaining.saturating_sub(1);
+            }
+            hits.send(PlayerHit {
+                position: player_position,
+                shielded,
+                lives_left: lives.remaining,
+            });
+            if lives.remaining == 0 {
+                deaths.send(PlayerDied {
+                    player: player_entity,
+                    position: player_position,
+                });
+            } else {
+                let away = player_position - enemy_transform.translation.truncate();
+                commands.entity(player_entity).insert((
+                    Invulnerable {
+                        timer: Timer::from_seconds(config.invulnerability_time, TimerMode::Once),
+                    },
+                    Knockback {
+                        velocity: away.normalize_or_zero() * config.knockback_speed,
+                    },
+                ));
+            }
+        }
+    }
+}
+
+pub fn end_run_on_player_death(
+    mut deaths: EventReader<PlayerDied>,
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for death in deaths.iter() {
+        commands.entity(death.player).despawn();
+        next_state.set(AppState::GameOver);
+    }
+}
+
+pub fn tick_invulnerability(
+    mut player_query: Query<(Entity, &mut Invulnerable, &mut Visibility), With<Player>>,
+    mut commands: Commands,
+    fixed_time: Res<FixedTime>,
+) {
+    for (entity, mut invulnerable, mut visibility) in player_query.iter_mut() {
+        invulnerable.timer.tick(fixed_time.period);
+        if invulnerable.timer.finished() {
+            *visibility = Visibility::Inherited;
+            commands.entity(entity).remove::<Invulnerable>();
+            continue;
+        }
+        let blink = (invulnerable.timer.elapsed_secs() / BLINK_INTERVAL) as u32 % 2 == 1;
+        *visibility = if blink { Visibility::Hidden } else { Visibility::Inherited };
+    }
+}
+
+pub fn apply_knockback(
+    mut player_query: Query<(Entity, &mut Transform, &mut Knockback), With<Player>>,
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    fixed_time: Res<FixedTime>,
+) {
+    let delta = fixed_time.period.as_secs_f32();
+    for (entity, mut transform, mut knockback) in player_query.iter_mut() {
+        transform.translation += knockback.velocity.extend(0.0) * delta;
+        knockback.velocity *= (1.0 - config.knockback_damping * delta).max(0.0);
+        if knockback.velocity.length() < 1.0 {
+            commands.entity(entity).remove::<Knockback>();
+        }
+    }
+}
+
+pub fn player_star_collision(
+    mut collisions: EventReader<CollisionStarted>,
+    player_query: Query<Entity, With<Player>>,
+    star_query: Query<(Entity, &Transform), With<Star>>,
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool>,
+    mut collected: EventWriter<StarCollected>,
+){
+    if let Ok(player) = player_query.get_single() {
+        for collision in collisions.iter() {
+            let Some((star, star_transform)) = collision.other(player).and_then(|other| star_query.get(other).ok()) else {
+                continue;
+            };
+            pool.release(&mut commands, star, PoolKind::Star);
+            collected.send(StarCollected {
+                star,
+                position: star_transform.translation.truncate(),
+            });
+        }
+    }
+
+}
+
+pub fn score_collected_stars(
+    mut collected: EventReader<StarCollected>,
+    mut score: ResMut<Score>,
+) {
+    let stars = collected.iter().count() as u32;
+    if stars > 0 {
+        score.value += stars;
+    }
+}
+
+pub fn update_score(score: Res<Score>) {
+    if score.is_changed() {
+        println!("Score: {}", score.value);
+    }
+}
+
+pub fn tick_survival_time(
+    mut survival_time: ResMut<SurvivalTime>,
+    fixed_time: Res<FixedTime>,
+) {
+    survival_time.seconds += fixed_time.period.as_secs_f32();
+}
+
+pub fn tick_star_spawn_timer(
+    mut star_spawn_timer: ResMut<StarSpawnTimer>,
+    fixed_time: Res<FixedTime>,
+){
+    star_spawn_timer.timer.tick(fixed_time.period);
+}
+
+pub fn tick_enemy_spawn_timer(
+    mut enemy_spawn_timer: ResMut<EnemySpawnTimer>,
+    fixed_time: Res<FixedTime>,
+) {
+    enemy_spawn_timer.timer.tick(fixed_time.period);
+}
+
+pub fn spawn_stars_over_time(
+    mut commands: Commands,
+    arena: Res<ArenaBounds>,
+    game_assets: Res<GameAssets>,
+    star_spawn_timer: Res<StarSpawnTimer>,
+    config: Res<GameConfig>,
+    star_query: Query<(), With<Star>>,
+    mut pool: ResMut<EntityPool>,
+    mut rng: ResMut<SimulationRng>,
+) {
+    if star_spawn_timer.timer.finished() && star_query.iter().count() < config.max_stars {
+        let random_x: f32 = rng.gen::<f32>() * arena.width;
+        let random_y: f32 = rng.gen::<f32>() * arena.height;
+
+        spawn_star(&mut commands, &mut pool, &game_assets, &config, Vec2::new(random_x, random_y));
+    }
+}
+
+pub fn spawn_enemy_over_time(
+    mut commands: Commands,
+    arena: Res<ArenaBounds>,
+    game_assets: Res<GameAssets>,
+    enemy_spawn_timer: Res<EnemySpawnTimer>,
+    config: Res<GameConfig>,
+    enemy_query: Query<(), With<Enemy>>,
+    player_query: Query<&Transform, With<Player>>,
+    mut pool: ResMut<EntityPool>,
+    mut rng: ResMut<SimulationRng>,
+    mut enemy_spawned: EventWriter<EnemySpawned>,
+) {
+    let room = config.max_enemies.saturating_sub(enemy_query.iter().count());
+    if enemy_spawn_timer.timer.finished() && room > 0 {
+        let pattern = SpawnPattern::random_timed(&mut *rng);
+        let count = pattern.group_size().min(room);
+        let player_position = player_position(&player_query, &arena);
+        for point in plan_spawns(pattern, count, &arena, player_position, &mut *rng) {
+            let kind = EnemyKind::random(&mut *rng);
+            spawn_enemy_of_kind(&mut commands, &mut pool, &game_assets, kind, point.position, point.direction, &mut enemy_spawned);
+        }
+    }
+}
+
+// fades out anything with a Lifetime and hands expired stars back to the pool
+pub fn tick_lifetimes(
+    mut commands: Commands,
+    fixed_time: Res<FixedTime>,
+    mut pool: ResMut<EntityPool>,
+    mut lifetime_query: Query<(Entity, &mut Lifetime, Option<&mut TextureAtlasSprite>, Option<&Star>)>,
+) {
+    for (entity, mut lifetime, sprite, star) in lifetime_query.iter_mut() {
+        lifetime.timer.tick(fixed_time.period);
+        if lifetime.timer.finished() {
+            if star.is_some() {
+                pool.release(&mut commands, entity, PoolKind::Star);
+            } else {
+                commands.entity(entity).despawn();
+            }
+            continue;
+        }
+        let remaining = lifetime.timer.duration().as_secs_f32() - lifetime.timer.elapsed_secs();
+        if let (Some(mut sprite), true) = (sprite, remaining < lifetime.fade_out) {
+            sprite.color.set_a(remaining / lifetime.fade_out);
+        }
+    }
+}
+
+pub fn exit_game(
+    actions: Res<Input<Action>>,
+    mut app_exit_event_write: EventWriter<AppExit>,
+) {
+    if actions.just_pressed(Action::Quit) {
+        app_exit_event_write.send(AppExit);
+    }
+}
@@ -0,0 +1,331 @@
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+};
+use game_common::{arena::ArenaBounds, score::Score};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::components::{Enemy, Player, Star};
+use crate::config::GameConfig;
+use crate::director::{plan_spawns, SpawnPattern};
+use crate::enemies::{spawn_enemy_of_kind, EnemyKind};
+use crate::events::EnemySpawned;
+use crate::pool::EntityPool;
+use crate::resources::{GameAssets, SimulationRng};
+use crate::ron_asset::{RonAsset, RonAssetPlugin};
+use crate::states::{reset_run_resources, RunStart};
+use crate::systems::{player_position, spawn_star};
+
+pub const WAVE_SCRIPT_PATH: &str = "waves/star_collector.waves.ron";
+pub const MAX_WAVE_GROUP_SIZE: usize = 50;
+pub const ENDLESS_WAVE_DELAY: f32 = 3.0;
+
+// wave bookkeeping for the simulation. The script itself starts out empty, which makes every
+// wave procedural, until WaveScriptPlugin loads the file
+pub struct WavesPlugin;
+
+impl Plugin for WavesPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<WaveScript>()
+        .init_resource::<WaveController>()
+        .add_event::<WaveStarted>()
+        .add_systems(RunStart, reset_waves.after(reset_run_resources));
+    }
+}
+
+// loads the wave script from the assets folder and swaps it in whenever the file changes.
+// a changed script only affects the waves that haven't been announced yet
+pub struct WaveScriptPlugin;
+
+impl Plugin for WaveScriptPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<WaveScript>::new(WAVE_SCRIPT_PATH).held_during_replays());
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct WaveGroup {
+    pub kind: EnemyKind,
+    pub count: usize,
+}
+
+// what has to happen before the next wave is announced
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ClearCondition {
+    Survive(f32),
+    CollectStars(u32),
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct WaveDefinition {
+    pub enemies: Vec<WaveGroup>,
+    pub pattern: SpawnPattern,
+    // seconds between the announcement and the enemies arriving
+    #[serde(default)]
+    pub delay: f32,
+    // extra stars dropped in when the enemies arrive
+    #[serde(default)]
+    pub star_bonus: usize,
+    pub clear: ClearCondition,
+}
+
+impl WaveDefinition {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enemies.is_empty() {
+            return Err("has no enemies".to_string());
+        }
+        if let Some(group) = self.enemies.iter().find(|group| group.count == 0 || group.count > MAX_WAVE_GROUP_SIZE) {
+            return Err(format!("spawns {} {:?}s, it must be between 1 and {}", group.count, group.kind, MAX_WAVE_GROUP_SIZE));
+        }
+        if !self.delay.is_finite() || self.delay < 0.0 {
+            return Err(format!("has a delay of {}, it can't be negative", self.delay));
+        }
+        match self.clear {
+            ClearCondition::Survive(seconds) if !seconds.is_finite() || seconds <= 0.0 => {
+                Err(format!("has to be survived for {} seconds, it must be more than 0", seconds))
+            }
+            ClearCondition::CollectStars(0) => Err("clears after collecting 0 stars".to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+// the scripted waves, played in order before endless mode takes over
+#[derive(Resource, Serialize, Deserialize, TypeUuid, TypePath, Clone, PartialEq, Debug, Default)]
+#[uuid = "9d2a6f41-7c3e-4b85-a1d0-3e5f8b7c2a96"]
+pub struct WaveScript {
+    pub waves: Vec<WaveDefinition>,
+}
+
+impl RonAsset for WaveScript {
+    const NAME: &'static str = "wave script";
+    const EXTENSIONS: &'static [&'static str] = &["waves.ron"];
+
+    fn validate(&self) -> Result<(), String> {
+        for (index, wave) in self.waves.iter().enumerate() {
+            wave.validate().map_err(|error| format!("wave {} {}", index + 1, error))?;
+        }
+        Ok(())
+    }
+}
+
+// endless mode: every wave after the script runs out gets a little bigger
+pub fn generate_wave(number: u32, rng: &mut impl Rng) -> WaveDefinition {
+    let groups = 1 + (number as usize / 4).min(3);
+    let count = 1 + number as usize / 3;
+    let enemies = (0..groups)
+        .map(|_| WaveGroup {
+            kind: EnemyKind::random(rng),
+            count,
+        })
+        .collect();
+    let pattern = SpawnPattern::random_timed(rng);
+    WaveDefinition {
+        enemies,
+        pattern,
+        delay: ENDLESS_WAVE_DELAY,
+        star_bonus: 2 + number as usize / 4,
+        clear: if number.is_multiple_of(2) {
+            ClearCondition::CollectStars(3 + number / 3)
+        } else {
+            ClearCondition::Survive(12.0 + number as f32)
+        },
+    }
+}
+
+pub enum WavePhase {
+    // announced, waiting out the delay before the enemies arrive
+    Incoming(Timer),
+    Active { elapsed: f32, score_at_start: u32 },
+}
+
+#[derive(Resource)]
+pub struct WaveController {
+    // 0 until the first wave is announced
+    pub number: u32,
+    pub wave: Option<WaveDefinition>,
+    pub phase: WavePhase,
+}
+
+impl Default for WaveController {
+    fn default() -> WaveController {
+        WaveController {
+            number: 0,
+            wave: None,
+            phase: WavePhase::Incoming(Timer::default()),
+        }
+    }
+}
+
+impl WaveController {
+    pub fn is_endless(&self, script: &WaveScript) -> bool {
+        self.number as usize > script.waves.len()
+    }
+
+    fn advance(&mut self, script: &WaveScript, rng: &mut impl Rng) {
+        self.number += 1;
+        let wave = match script.waves.get(self.number as usize - 1) {
+            Some(wave) => wave.clone(),
+            None => generate_wave(self.number, rng),
+        };
+        self.phase = WavePhase::Incoming(Timer::from_seconds(wave.delay, TimerMode::Once));
+        self.wave = Some(wave);
+    }
+}
+
+#[derive(Event)]
+pub struct WaveStarted {
+    pub number: u32,
+    pub endless: bool,
+}
+
+// the first wave is announced from inside the fixed step so it draws from the rng in order
+pub fn reset_waves(mut controller: ResMut<WaveController>) {
+    *controller = WaveController::default();
+}
+
+// everything needed to put a wave's enemies and stars on the field
+#[derive(SystemParam)]
+pub struct WaveSpawner<'w, 's> {
+    pub commands: Commands<'w, 's>,
+    pub arena: Res<'w, ArenaBounds>,
+    pub game_assets: Res<'w, GameAssets>,
+    pub config: Res<'w, GameConfig>,
+    pub pool: ResMut<'w, EntityPool>,
+    pub enemy_query: Query<'w, 's, (), With<Enemy>>,
+    pub star_query: Query<'w, 's, (), With<Star>>,
+    pub player_query: Query<'w, 's, &'static Transform, With<Player>>,
+    pub enemy_spawned: EventWriter<'w, EnemySpawned>,
+}
+
+impl WaveSpawner<'_, '_> {
+    // as many of the wave's enemies and bonus stars as fit under the caps
+    pub fn spawn_wave(&mut self, wave: &WaveDefinition, rng: &mut impl Rng) {
+        let player_position = player_position(&self.player_query, &self.arena);
+        let mut room = self.config.max_enemies.saturating_sub(self.enemy_query.iter().count());
+        for group in wave.enemies.iter() {
+            let count = group.count.min(room);
+            room -= count;
+            for point in plan_spawns(wave.pattern, count, &self.arena, player_position, rng) {
+                spawn_enemy_of_kind(&mut self.commands, &mut self.pool, &self.game_assets, group.kind, point.position, point.direction, &mut self.enemy_spawned);
+            }
+        }
+        let star_room = self.config.max_stars.saturating_sub(self.star_query.iter().count());
+        for _ in 0..wave.star_bonus.min(star_room) {
+            let position = Vec2::new(rng.gen::<f32>() * self.arena.width, rng.gen::<f32>() * self.arena.height);
+            spawn_star(&mut self.commands, &mut self.pool, &self.game_assets, &self.config, position);
+        }
+    }
+}
+
+pub fn update_waves(
+    mut spawner: WaveSpawner,
+    script: Res<WaveScript>,
+    score: Res<Score>,
+    fixed_time: Res<FixedTime>,
+    mut controller: ResMut<WaveController>,
+    mut rng: ResMut<SimulationRng>,
+    mut wave_started: EventWriter<WaveStarted>,
+) {
+    let controller = &mut *controller;
+    let Some(wave) = &controller.wave else {
+        controller.advance(&script, &mut *rng);
+        wave_started.send(WaveStarted {
+            number: controller.number,
+            endless: controller.is_endless(&script),
+        });
+        return;
+    };
+
+    match &mut controller.phase {
+        WavePhase::Incoming(timer) => {
+            timer.tick(fixed_time.period);
+            if !timer.finished() {
+                return;
+            }
+            spawner.spawn_wave(wave, &mut *rng);
+            controller.phase = WavePhase::Active {
+                elapsed: 0.0,
+                score_at_start: score.value,
+            };
+        }
+        WavePhase::Active { elapsed, score_at_start } => {
+            *elapsed += fixed_time.period.as_secs_f32();
+            let cleared = match wave.clear {
+                ClearCondition::Survive(seconds) => *elapsed >= seconds,
+                ClearCondition::CollectStars(stars) => score.value.saturating_sub(*score_at_start) >= stars,
+            };
+            if cleared {
+                controller.advance(&script, &mut *rng);
+                wave_started.send(WaveStarted {
+                    number: controller.number,
+                    endless: controller.is_endless(&script),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wave() -> WaveDefinition {
+        WaveDefinition {
+            enemies: vec![WaveGroup { kind: EnemyKind::Bouncer, count: 2 }],
+            pattern: SpawnPattern::EdgeEntry,
+            delay: 1.0,
+            star_bonus: 0,
+            clear: ClearCondition::Survive(10.0),
+        }
+    }
+
+    // a good first wave followed by the one under test
+    fn script(last: WaveDefinition) -> WaveScript {
+        WaveScript { waves: vec![wave(), last] }
+    }
+
+    #[test]
+    fn shipped_script_is_valid() {
+        let shipped: WaveScript = ron::from_str(include_str!("../assets/waves/star_collector.waves.ron")).unwrap();
+        assert!(!shipped.waves.is_empty());
+        assert_eq!(shipped.validate(), Ok(()));
+    }
+
+    #[test]
+    fn errors_name_the_wave() {
+        let empty = WaveDefinition { enemies: Vec::new(), ..wave() };
+        assert_eq!(script(empty).validate(), Err("wave 2 has no enemies".to_string()));
+    }
+
+    #[test]
+    fn group_sizes_are_checked() {
+        let none = WaveDefinition { enemies: vec![WaveGroup { kind: EnemyKind::Chaser, count: 0 }], ..wave() };
+        assert!(script(none).validate().is_err());
+        let too_many = WaveDefinition {
+            enemies: vec![WaveGroup { kind: EnemyKind::Chaser, count: MAX_WAVE_GROUP_SIZE + 1 }],
+            ..wave()
+        };
+        assert!(script(too_many).validate().is_err());
+    }
+
+    #[test]
+    fn delay_and_clear_conditions_are_checked() {
+        assert!(script(WaveDefinition { delay: -1.0, ..wave() }).validate().is_err());
+        assert!(script(WaveDefinition { delay: f32::NAN, ..wave() }).validate().is_err());
+        assert!(script(WaveDefinition { clear: ClearCondition::Survive(0.0), ..wave() }).validate().is_err());
+        assert!(script(WaveDefinition { clear: ClearCondition::CollectStars(0), ..wave() }).validate().is_err());
+        assert_eq!(script(WaveDefinition { clear: ClearCondition::CollectStars(1), ..wave() }).validate(), Ok(()));
+    }
+
+    #[test]
+    fn endless_waves_are_valid() {
+        let mut rng = SimulationRng::new(Some(7));
+        for number in 1..40 {
+            assert_eq!(generate_wave(number, &mut rng).validate(), Ok(()));
+        }
+    }
+}
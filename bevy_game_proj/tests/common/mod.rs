@@ -0,0 +1,43 @@
+// helpers shared by the integration tests, each test file pulls this in with `mod common;`
+#![allow(dead_code)]
+
+use bevy::prelude::*;
+use bevy_game_proj::states::AppState;
+
+pub const RUN_TICKS: u32 = 600;
+// far more updates than starting a run takes, so a broken start fails instead of hanging
+pub const MAX_START_UPDATES: u32 = 10;
+
+pub fn state(app: &App) -> AppState {
+    *app.world.resource::<State<AppState>>().get()
+}
+
+// steps until the run is under way, plays RUN_TICKS ticks of it (fewer if the player dies) and
+// ends it, which is when a recording is written and a playback verified. `steer` is handed the
+// tick number before each one
+pub fn play_run(app: &mut App, mut steer: impl FnMut(&mut Input<KeyCode>, u32)) {
+    let mut updates = 0;
+    while state(app) != AppState::InGame {
+        assert!(updates < MAX_START_UPDATES, "the run never started");
+        app.update();
+        updates += 1;
+    }
+    for tick in 0..RUN_TICKS {
+        if state(app) == AppState::GameOver {
+            return;
+        }
+        steer(&mut app.world.resource_mut::<Input<KeyCode>>(), tick);
+        app.update();
+    }
+    app.world.resource_mut::<NextState<AppState>>().set(AppState::GameOver);
+    app.update();
+}
+
+// zig-zags across the arena so a run gets plenty of different inputs
+pub fn zig_zag(keyboard: &mut Input<KeyCode>, tick: u32) {
+    keyboard.release_all();
+    keyboard.press(if (tick / 40).is_multiple_of(2) { KeyCode::D } else { KeyCode::A });
+    if (tick / 25).is_multiple_of(3) {
+        keyboard.press(KeyCode::W);
+    }
+}
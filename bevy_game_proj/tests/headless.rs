@@ -0,0 +1,230 @@
+mod common;
+
+use bevy::{app::AppExit, prelude::*, window::WindowFocused};
+use bevy_game_proj::{
+    components::{Enemy, Invulnerable, Lives, Player, Star, ENEMY_COLLISION},
+    config::GameConfig,
+    enemies::EnemyKind,
+    headless_app,
+    hud::{HudPlugin, LivesText},
+    pause_menu::PauseMenuPlugin,
+    resources::{SimulationRng, SurvivalTime},
+    states::{AppState, MainMenuScreen},
+    stats::RunStats,
+    ENEMY_SIZE, FIXED_TIMESTEP, NUMBER_OF_ENEMIES, NUMBER_OF_STARS,
+};
+use common::{play_run, state, zig_zag};
+use game_common::{arena::ArenaBounds, collision::Collider, score::Score};
+
+// smaller than the default window, so anything still placed by the window size lands outside it
+const ARENA: ArenaBounds = ArenaBounds { width: 400.0, height: 300.0 };
+
+fn positions<T: Component>(app: &mut App) -> Vec<Vec3> {
+    app.world
+        .query_filtered::<&Transform, With<T>>()
+        .iter(&app.world)
+        .map(|transform| transform.translation)
+        .collect()
+}
+
+// holds a key for two updates. The first reads it and asks for a state change, the second applies it
+fn tap(app: &mut App, key: KeyCode) {
+    app.world.resource_mut::<Input<KeyCode>>().press(key);
+    app.update();
+    app.update();
+    app.world.resource_mut::<Input<KeyCode>>().release(key);
+}
+
+fn start_run(app: &mut App) {
+    app.world.resource_mut::<NextState<AppState>>().set(AppState::InGame);
+    app.update();
+    assert_eq!(state(app), AppState::InGame);
+}
+
+#[test]
+fn a_run_starts_inside_the_virtual_arena() {
+    let mut app = headless_app(ARENA);
+    start_run(&mut app);
+
+    let enemies = positions::<Enemy>(&mut app);
+    let stars = positions::<Star>(&mut app);
+    assert_eq!(enemies.len(), NUMBER_OF_ENEMIES);
+    assert_eq!(stars.len(), NUMBER_OF_STARS);
+    for position in enemies.iter().chain(stars.iter()) {
+        assert!((0.0..=ARENA.width).contains(&position.x), "{:?} is outside the arena", position);
+        assert!((0.0..=ARENA.height).contains(&position.y), "{:?} is outside the arena", position);
+    }
+    let camera = positions::<Camera>(&mut app);
+    assert_eq!((camera[0].x, camera[0].y), (ARENA.width / 2.0, ARENA.height / 2.0));
+}
+
+#[test]
+fn every_update_advances_the_run_by_one_frame() {
+    let mut app = headless_app(ArenaBounds::default());
+    start_run(&mut app);
+    // enemies spawn at random, so clear them out to keep the player alive for the whole test
+    let enemies: Vec<Entity> = app.world.query_filtered::<Entity, With<Enemy>>().iter(&app.world).collect();
+    for enemy in enemies {
+        app.world.despawn(enemy);
+    }
+
+    let started_at = app.world.resource::<SurvivalTime>().seconds;
+    for _ in 0..60 {
+        app.update();
+    }
+    assert_eq!(state(&app), AppState::InGame);
+    assert_eq!(positions::<Player>(&mut app).len(), 1);
+    let survived = app.world.resource::<SurvivalTime>().seconds - started_at;
+    assert!((survived - 60.0 * FIXED_TIMESTEP).abs() < 1e-3, "survived {}s", survived);
+}
+
+#[test]
+fn escape_pauses_the_run_instead_of_quitting() {
+    let mut app = headless_app(ArenaBounds::default());
+    start_run(&mut app);
+
+    tap(&mut app, KeyCode::Escape);
+    assert_eq!(state(&app), AppState::Paused);
+    assert!(app.world.resource::<Events<AppExit>>().is_empty());
+
+    let paused_at = app.world.resource::<SurvivalTime>().seconds;
+    for _ in 0..30 {
+        app.update();
+    }
+    assert_eq!(app.world.resource::<SurvivalTime>().seconds, paused_at);
+
+    tap(&mut app, KeyCode::P);
+    assert_eq!(state(&app), AppState::InGame);
+}
+
+// pauses the run and picks Restart on the pause menu, which the app needs to have been given
+fn restart_from_pause_menu(app: &mut App) {
+    tap(app, KeyCode::Escape);
+    assert_eq!(state(app), AppState::Paused);
+    // down from Resume to Restart, then pick it
+    tap(app, KeyCode::Down);
+    tap(app, KeyCode::Space);
+    let mut updates = 0;
+    while state(app) != AppState::InGame {
+        assert_ne!(state(app), AppState::MainMenu);
+        assert!(updates < 5, "the restarted run never started");
+        app.update();
+        updates += 1;
+    }
+}
+
+#[test]
+fn restart_from_the_pause_menu_skips_the_main_menu() {
+    let mut app = headless_app(ArenaBounds::default());
+    // the pause menu reads focus changes, which the windowless app never sends
+    app.add_plugins(PauseMenuPlugin).add_event::<WindowFocused>();
+    start_run(&mut app);
+    for _ in 0..60 {
+        app.update();
+    }
+    assert!(app.world.resource::<SurvivalTime>().seconds > 0.0);
+
+    restart_from_pause_menu(&mut app);
+    assert!(app.world.query::<&MainMenuScreen>().iter(&app.world).next().is_none());
+    // the new run may already be a tick in
+    assert!(app.world.resource::<SurvivalTime>().seconds <= FIXED_TIMESTEP + 1e-6);
+    assert_eq!(app.world.resource::<Score>().value, 0);
+    assert_eq!(positions::<Player>(&mut app).len(), 1);
+}
+
+#[test]
+fn restart_keeps_the_new_runs_lives_on_the_hud() {
+    let mut app = headless_app(ArenaBounds::default());
+    app.add_plugins((PauseMenuPlugin, HudPlugin)).add_event::<WindowFocused>();
+    start_run(&mut app);
+    restart_from_pause_menu(&mut app);
+    for _ in 0..5 {
+        app.update();
+    }
+    let lives = app.world.resource::<GameConfig>().player_lives;
+    let text = app.world.query_filtered::<&Text, With<LivesText>>().single(&app.world).sections[0].value.clone();
+    assert_eq!(text, format!("Lives: {}", lives));
+}
+
+fn player_lives(app: &mut App) -> Option<u32> {
+    app.world.query_filtered::<&Lives, With<Player>>().iter(&app.world).next().map(|lives| lives.remaining)
+}
+
+// moves the enemy onto the player, who gets knocked away by every hit
+fn pin_on_player(app: &mut App, enemy: Entity) {
+    if let Some(player) = positions::<Player>(app).first().copied() {
+        app.world.get_mut::<Transform>(enemy).unwrap().translation = player;
+    }
+}
+
+#[test]
+fn hits_cost_a_life_and_only_the_last_one_ends_the_run() {
+    let mut app = headless_app(ArenaBounds::default());
+    start_run(&mut app);
+    let enemies: Vec<Entity> = app.world.query_filtered::<Entity, With<Enemy>>().iter(&app.world).collect();
+    for enemy in enemies {
+        app.world.despawn(enemy);
+    }
+    // already past its telegraph, so it can hit straight away
+    let enemy = app.world.spawn((
+        Transform::default(),
+        Enemy { direction: Vec2::X, kind: EnemyKind::Bouncer, speed: 0.0 },
+        Collider::circle(ENEMY_SIZE / 2.0, ENEMY_COLLISION),
+    )).id();
+    let config = app.world.resource::<GameConfig>().clone();
+    let invulnerable_updates = (config.invulnerability_time / FIXED_TIMESTEP) as usize;
+
+    for remaining in (1..config.player_lives).rev() {
+        let mut updates = 0;
+        while player_lives(&mut app) != Some(remaining) {
+            // the next hit waits out what is left of the last invulnerability
+            assert!(updates < invulnerable_updates + 10, "the enemy never hit the player");
+            pin_on_player(&mut app, enemy);
+            app.update();
+            updates += 1;
+        }
+        assert_eq!(state(&app), AppState::InGame);
+        let player = app.world.query_filtered::<Entity, With<Player>>().single(&app.world);
+        assert!(app.world.get::<Invulnerable>(player).is_some());
+
+        // touching the player again while it's invulnerable costs nothing
+        for _ in 0..invulnerable_updates / 2 {
+            pin_on_player(&mut app, enemy);
+            app.update();
+        }
+        assert_eq!(player_lives(&mut app), Some(remaining));
+        assert_eq!(state(&app), AppState::InGame);
+    }
+
+    // the last life goes with the player, and the run ends on the next update
+    let mut updates = 0;
+    while player_lives(&mut app).is_some() {
+        assert!(updates < invulnerable_updates + 10, "the last hit never landed");
+        assert_eq!(state(&app), AppState::InGame);
+        pin_on_player(&mut app, enemy);
+        app.update();
+        updates += 1;
+    }
+    app.update();
+    assert_eq!(state(&app), AppState::GameOver);
+}
+
+// plays a whole run on the given seed and hands back the app as the run ended
+fn run_with_seed(seed: u64) -> App {
+    let mut app = headless_app(ArenaBounds::default());
+    app.world.resource_mut::<SimulationRng>().fixed_seed = Some(seed);
+    app.world.resource_mut::<NextState<AppState>>().set(AppState::InGame);
+    play_run(&mut app, zig_zag);
+    app
+}
+
+#[test]
+fn same_seed_and_input_give_the_same_run() {
+    let mut first = run_with_seed(42);
+    let mut second = run_with_seed(42);
+    assert_eq!(first.world.resource::<Score>().value, second.world.resource::<Score>().value);
+    assert_eq!(first.world.resource::<RunStats>(), second.world.resource::<RunStats>());
+    assert_eq!(first.world.resource::<SurvivalTime>().seconds, second.world.resource::<SurvivalTime>().seconds);
+    assert_eq!(positions::<Enemy>(&mut first), positions::<Enemy>(&mut second));
+    assert_eq!(positions::<Star>(&mut first), positions::<Star>(&mut second));
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_game_proj::{
+    headless_app,
+    replay::{read_replay, write_replay, Replay, ReplayMode, ReplayState, ReplayVerification, REPLAY_VERSION},
+    states::AppState,
+};
+use common::{play_run, zig_zag};
+use game_common::arena::ArenaBounds;
+
+#[test]
+fn recorded_run_plays_back_to_the_same_score() {
+    let path = std::env::temp_dir().join(format!("star_collector_replay_{}.ron", std::process::id()));
+    let recorded_arena = ArenaBounds { width: 1000.0, height: 600.0 };
+
+    let mut recorder = headless_app(recorded_arena);
+    recorder.insert_resource(ReplayMode::Record(path.clone()));
+    recorder.world.resource_mut::<NextState<AppState>>().set(AppState::InGame);
+    play_run(&mut recorder, zig_zag);
+
+    let replay = read_replay(&path).expect("the recording should have been written");
+    assert!(replay.ticks > 0);
+    assert_eq!(replay.arena, recorded_arena);
+
+    // played back in a differently sized arena, which the replay has to override
+    let mut player = headless_app(ArenaBounds::default());
+    player.insert_resource(ReplayMode::Playback(path.clone()));
+    // the keyboard is ignored while playing back
+    play_run(&mut player, |keyboard, _| keyboard.press(KeyCode::S));
+
+    assert_eq!(*player.world.resource::<ArenaBounds>(), recorded_arena);
+    assert_eq!(player.world.resource::<ReplayState>().verification, Some(ReplayVerification::Matched));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn replay_from_another_version_is_refused() {
+    let path = std::env::temp_dir().join(format!("star_collector_old_replay_{}.ron", std::process::id()));
+    write_replay(&path, &Replay { version: REPLAY_VERSION + 1, ..default() }).unwrap();
+    assert!(read_replay(&path).is_err());
+
+    let mut player = headless_app(ArenaBounds::default());
+    player.insert_resource(ReplayMode::Playback(path.clone()));
+    player.update();
+    assert_eq!(*player.world.resource::<ReplayMode>(), ReplayMode::Off);
+    std::fs::remove_file(&path).unwrap();
+}
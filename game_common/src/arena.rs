@@ -0,0 +1,122 @@
+use bevy::{prelude::*, window::{PrimaryWindow, WindowResized}};
+use serde::{Deserialize, Serialize};
+
+// size of the play field, (0, 0) is the bottom left corner.
+// follows the primary window when there is one, otherwise whatever the headless app was given
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct ArenaBounds {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for ArenaBounds {
+    fn default() -> ArenaBounds {
+        ArenaBounds {
+            width: 1280.0,
+            height: 720.0,
+        }
+    }
+}
+
+impl ArenaBounds {
+    pub fn center(&self) -> Vec2 {
+        Vec2::new(self.width / 2.0, self.height / 2.0)
+    }
+
+    // closest point where something reaching `half_extents` out from its centre still fits inside.
+    // a window smaller than the object pins it to the bottom left
+    pub fn clamp(&self, position: Vec2, half_extents: Vec2) -> Vec2 {
+        Vec2::new(
+            position.x.clamp(half_extents.x, (self.width - half_extents.x).max(half_extents.x)),
+            position.y.clamp(half_extents.y, (self.height - half_extents.y).max(half_extents.y)),
+        )
+    }
+}
+
+// set while something other than the window decides the arena's size, e.g. a replay that has to
+// play out in the arena it was recorded in
+#[derive(Resource, Default)]
+pub struct ArenaLocked(pub bool);
+
+// kept fully inside the arena by confine_to_arena
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct ConfineToArena {
+    pub half_extents: Vec2,
+}
+
+impl ConfineToArena {
+    pub fn new(size: Vec2) -> ConfineToArena {
+        ConfineToArena {
+            half_extents: size / 2.0,
+        }
+    }
+
+    pub fn square(size: f32) -> ConfineToArena {
+        ConfineToArena::new(Vec2::splat(size))
+    }
+}
+
+pub fn confine_to_arena(
+    arena: Res<ArenaBounds>,
+    mut confined_query: Query<(&mut Transform, &ConfineToArena)>,
+) {
+    for (mut transform, confine) in confined_query.iter_mut() {
+        let inside = arena.clamp(transform.translation.truncate(), confine.half_extents);
+        transform.translation.x = inside.x;
+        transform.translation.y = inside.y;
+    }
+}
+
+pub fn sync_arena_to_window(
+    mut arena: ResMut<ArenaBounds>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    if let Ok(window) = window_query.get_single() {
+        arena.width = window.width();
+        arena.height = window.height();
+    }
+}
+
+pub fn spawn_camera(mut commands: Commands, arena: Res<ArenaBounds>) {
+    let center = arena.center();
+    commands.spawn(Camera2dBundle {
+        transform: Transform::from_xyz(center.x, center.y, 0.0),
+        ..default()
+    });
+}
+
+// keeps the arena matched to the window unless it is locked. Anything the shrinking window left
+// outside is pulled back in by confine_to_arena on the next tick
+pub fn follow_window_resize(
+    mut resized_events: EventReader<WindowResized>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    arena_locked: Res<ArenaLocked>,
+    mut arena: ResMut<ArenaBounds>,
+) {
+    let Ok(primary_window) = window_query.get_single() else {
+        return;
+    };
+    let Some(resized) = resized_events.iter().filter(|event| event.window == primary_window).last() else {
+        return;
+    };
+    if arena_locked.0 {
+        return;
+    }
+    arena.width = resized.width;
+    arena.height = resized.height;
+}
+
+// the camera stays centred on the arena however it changed size
+pub fn center_camera_on_arena(
+    arena: Res<ArenaBounds>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+) {
+    if !arena.is_changed() {
+        return;
+    }
+    let center = arena.center();
+    for mut camera_transform in camera_query.iter_mut() {
+        camera_transform.translation.x = center.x;
+        camera_transform.translation.y = center.y;
+    }
+}
@@ -0,0 +1,320 @@
+use std::{collections::BTreeMap, fmt::Debug, hash::Hash, marker::PhantomData, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::ron_file::{load_or_create_ron_file, write_ron_file};
+use crate::settings_menu::{RebindKeys, SettingsClosed};
+
+pub const DEFAULT_STICK_DEADZONE: f32 = 0.15;
+pub const MAX_STICK_DEADZONE: f32 = 0.95;
+
+// the actions a game lets the player bind. Each game has its own enum, the bindings file,
+// the settings screen and the deadzone handling are shared
+pub trait GameAction: Serialize + DeserializeOwned + Copy + Ord + Hash + Debug + Send + Sync + 'static {
+    fn default_bindings() -> BTreeMap<Self, Binding>;
+    // shown on the settings screen
+    fn label(&self) -> &'static str;
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Binding {
+    pub keys: Vec<KeyCode>,
+    pub gamepad_buttons: Vec<GamepadButtonType>,
+}
+
+impl Binding {
+    pub fn new(keys: &[KeyCode], gamepad_buttons: &[GamepadButtonType]) -> Binding {
+        Binding {
+            keys: keys.to_vec(),
+            gamepad_buttons: gamepad_buttons.to_vec(),
+        }
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default, bound = "A: GameAction")]
+pub struct InputBindings<A: GameAction> {
+    pub actions: BTreeMap<A, Binding>,
+    pub stick_deadzone: f32,
+}
+
+impl<A: GameAction> Default for InputBindings<A> {
+    fn default() -> InputBindings<A> {
+        InputBindings {
+            actions: A::default_bindings(),
+            stick_deadzone: DEFAULT_STICK_DEADZONE,
+        }
+    }
+}
+
+impl<A: GameAction> InputBindings<A> {
+    // fills in actions missing from an older file and pulls values back into range
+    pub fn validated(mut self) -> InputBindings<A> {
+        for (action, binding) in A::default_bindings() {
+            self.actions.entry(action).or_insert(binding);
+        }
+        // clamping leaves NaN as it is, which would make every stick reading NaN too
+        if !self.stick_deadzone.is_finite() {
+            warn!("stick_deadzone {} is not a number, resetting it", self.stick_deadzone);
+            self.stick_deadzone = DEFAULT_STICK_DEADZONE;
+        } else if !(0.0..=MAX_STICK_DEADZONE).contains(&self.stick_deadzone) {
+            warn!(
+                "stick_deadzone {} is outside 0.0..={}, clamping it",
+                self.stick_deadzone, MAX_STICK_DEADZONE
+            );
+            self.stick_deadzone = self.stick_deadzone.clamp(0.0, MAX_STICK_DEADZONE);
+        }
+        self
+    }
+
+    // presses every action with a bound key or button held down on any gamepad, and releases the rest
+    pub fn update_actions(
+        &self,
+        keyboard_input: &Input<KeyCode>,
+        gamepads: &Gamepads,
+        gamepad_buttons: &Input<GamepadButton>,
+        actions: &mut Input<A>,
+    ) {
+        actions.clear();
+        for (&action, binding) in self.actions.iter() {
+            let key_pressed = binding.keys.iter().any(|&key| keyboard_input.pressed(key));
+            let button_pressed = gamepads.iter().any(|gamepad| {
+                binding.gamepad_buttons.iter().any(|&button_type| {
+                    gamepad_buttons.pressed(GamepadButton::new(gamepad, button_type))
+                })
+            });
+            if key_pressed || button_pressed {
+                actions.press(action);
+            } else {
+                actions.release(action);
+            }
+        }
+    }
+}
+
+impl<A: GameAction> RebindKeys for InputBindings<A> {
+    fn key_rows(&self) -> Vec<(&'static str, Vec<KeyCode>)> {
+        self.actions.iter().map(|(action, binding)| (action.label(), binding.keys.clone())).collect()
+    }
+
+    // only the first key is replaced, so alternatives like WASD next to the arrows stay bound.
+    // an action that already had `key` gets the replaced key in its place, the two swap.
+    // gamepad buttons are left as they are
+    fn rebind(&mut self, row: usize, key: KeyCode) {
+        let Some(&action) = self.actions.keys().nth(row) else {
+            return;
+        };
+        let replaced = self.actions[&action].keys.first().copied();
+        let taken = self.actions.iter().any(|(&other, binding)| other != action && binding.keys.contains(&key));
+        if taken && replaced.is_none() {
+            // nothing to swap back, taking the key would leave the other action a key short
+            warn!("{:?} is already bound, not rebinding {:?}", key, action);
+            return;
+        }
+        for (&other, binding) in self.actions.iter_mut() {
+            if other == action {
+                match binding.keys.iter().position(|&bound| bound == key) {
+                    Some(index) => binding.keys.swap(0, index),
+                    None if binding.keys.is_empty() => binding.keys.push(key),
+                    None => binding.keys[0] = key,
+                }
+            } else if let Some(replaced) = replaced {
+                for bound in binding.keys.iter_mut().filter(|bound| **bound == key) {
+                    *bound = replaced;
+                }
+                dedup_keys(&mut binding.keys);
+            }
+        }
+    }
+}
+
+// keeps the first of any repeated keys, a swap can hand an action a key it already had
+fn dedup_keys(keys: &mut Vec<KeyCode>) {
+    let mut seen = Vec::with_capacity(keys.len());
+    keys.retain(|key| {
+        let first = !seen.contains(key);
+        seen.push(*key);
+        first
+    });
+}
+
+// rescales the stick so movement starts from zero at the edge of the deadzone instead of jumping.
+// a single axis can go through here as a Vec2 with the other component at zero
+pub fn apply_deadzone(stick: Vec2, deadzone: f32) -> Vec2 {
+    let length = stick.length();
+    if length <= deadzone {
+        return Vec2::ZERO;
+    }
+    let scaled_length = ((length - deadzone) / (1.0 - deadzone)).min(1.0);
+    stick / length * scaled_length
+}
+
+// loads the player's bindings from disk, writing the defaults out the first time so there is a file to edit,
+// and saves them again whenever the settings screen closes
+pub struct BindingsFilePlugin<A> {
+    // folder under the platform config dir the file goes in
+    pub app_dir: &'static str,
+    // set this env var to use a bindings file somewhere else
+    pub path_env: &'static str,
+    marker: PhantomData<fn() -> A>,
+}
+
+impl<A> BindingsFilePlugin<A> {
+    pub fn new(app_dir: &'static str, path_env: &'static str) -> BindingsFilePlugin<A> {
+        BindingsFilePlugin {
+            app_dir,
+            path_env,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<A: GameAction> Plugin for BindingsFilePlugin<A> {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<InputBindings<A>>()
+        .insert_resource(BindingsPath::new(self.app_dir, self.path_env))
+        // registered here too so the bindings plugin works without the settings screen
+        .add_event::<SettingsClosed>()
+        .add_systems(PreStartup, load_input_bindings::<A>)
+        .add_systems(Update, save_input_bindings::<A>);
+    }
+}
+
+#[derive(Resource)]
+pub struct BindingsPath {
+    pub path: PathBuf,
+}
+
+impl BindingsPath {
+    pub fn new(app_dir: &str, path_env: &str) -> BindingsPath {
+        let path = match std::env::var_os(path_env) {
+            Some(path) => PathBuf::from(path),
+            None => dirs::config_dir()
+                .map(|dir| dir.join(app_dir))
+                .unwrap_or_default()
+                .join("bindings.ron"),
+        };
+        BindingsPath { path }
+    }
+}
+
+pub fn load_input_bindings<A: GameAction>(
+    bindings_path: Res<BindingsPath>,
+    mut bindings: ResMut<InputBindings<A>>,
+) {
+    load_or_create_ron_file(&bindings_path.path, "bindings", &mut *bindings, InputBindings::validated);
+}
+
+// keys rebound on the settings screen are kept for next time
+pub fn save_input_bindings<A: GameAction>(
+    mut closed: EventReader<SettingsClosed>,
+    bindings_path: Res<BindingsPath>,
+    bindings: Res<InputBindings<A>>,
+) {
+    if closed.iter().count() == 0 {
+        return;
+    }
+    if let Err(err) = write_ron_file(&bindings_path.path, &*bindings) {
+        warn!("could not save bindings to {:?}: {}", bindings_path.path, err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+    enum TestAction {
+        MoveUp,
+        Confirm,
+        Pause,
+    }
+
+    impl GameAction for TestAction {
+        fn default_bindings() -> BTreeMap<TestAction, Binding> {
+            BTreeMap::from([
+                (TestAction::MoveUp, Binding::new(&[KeyCode::Up, KeyCode::W], &[GamepadButtonType::DPadUp])),
+                (TestAction::Confirm, Binding::new(&[KeyCode::Space], &[GamepadButtonType::South])),
+                (TestAction::Pause, Binding::new(&[], &[GamepadButtonType::Start])),
+            ])
+        }
+
+        fn label(&self) -> &'static str {
+            "test"
+        }
+    }
+
+    fn keys(bindings: &InputBindings<TestAction>, action: TestAction) -> Vec<KeyCode> {
+        bindings.actions[&action].keys.clone()
+    }
+
+    #[test]
+    fn rebind_replaces_only_the_first_key() {
+        let mut bindings = InputBindings::<TestAction>::default();
+        bindings.rebind(0, KeyCode::I);
+        assert_eq!(keys(&bindings, TestAction::MoveUp), vec![KeyCode::I, KeyCode::W]);
+        assert_eq!(bindings.actions[&TestAction::MoveUp].gamepad_buttons, vec![GamepadButtonType::DPadUp]);
+        // already one of its keys, it just moves to the front
+        bindings.rebind(0, KeyCode::W);
+        assert_eq!(keys(&bindings, TestAction::MoveUp), vec![KeyCode::W, KeyCode::I]);
+    }
+
+    #[test]
+    fn rebind_swaps_a_key_bound_to_another_action() {
+        let mut bindings = InputBindings::<TestAction>::default();
+        bindings.rebind(1, KeyCode::W);
+        assert_eq!(keys(&bindings, TestAction::Confirm), vec![KeyCode::W]);
+        assert_eq!(keys(&bindings, TestAction::MoveUp), vec![KeyCode::Up, KeyCode::Space]);
+    }
+
+    #[test]
+    fn rebind_refuses_a_taken_key_with_nothing_to_swap_back() {
+        let mut bindings = InputBindings::<TestAction>::default();
+        bindings.rebind(2, KeyCode::Space);
+        assert!(keys(&bindings, TestAction::Pause).is_empty());
+        assert_eq!(keys(&bindings, TestAction::Confirm), vec![KeyCode::Space]);
+        bindings.rebind(2, KeyCode::P);
+        assert_eq!(keys(&bindings, TestAction::Pause), vec![KeyCode::P]);
+    }
+
+    #[test]
+    fn validated_resets_deadzones_that_are_not_numbers() {
+        for deadzone in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            let bindings = InputBindings::<TestAction> { stick_deadzone: deadzone, ..default() }.validated();
+            assert_eq!(bindings.stick_deadzone, DEFAULT_STICK_DEADZONE);
+        }
+        let bindings = InputBindings::<TestAction> { stick_deadzone: 2.0, ..default() }.validated();
+        assert_eq!(bindings.stick_deadzone, MAX_STICK_DEADZONE);
+    }
+
+    #[test]
+    fn deadzone_swallows_small_movements() {
+        assert_eq!(apply_deadzone(Vec2::new(0.1, 0.05), 0.15), Vec2::ZERO);
+        assert_eq!(apply_deadzone(Vec2::new(0.15, 0.0), 0.15), Vec2::ZERO);
+    }
+
+    #[test]
+    fn deadzone_rescales_from_its_edge() {
+        let half_way = apply_deadzone(Vec2::new(0.0, 0.6), 0.2);
+        assert!((half_way.y - 0.5).abs() < 1e-6);
+        assert_eq!(half_way.x, 0.0);
+        let full = apply_deadzone(Vec2::new(1.0, 0.0), 0.2);
+        assert!((full.x - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn deadzone_keeps_the_direction_and_caps_the_length() {
+        // diagonal sticks can report a little past 1
+        let stick = Vec2::new(1.0, 1.0);
+        let scaled = apply_deadzone(stick, 0.1);
+        assert!((scaled.length() - 1.0).abs() < 1e-6);
+        assert!((scaled.normalize() - stick.normalize()).length() < 1e-6);
+    }
+
+    #[test]
+    fn single_axis_goes_through_as_a_vec2() {
+        assert!((apply_deadzone(Vec2::new(0.0, -0.6), 0.2).y + 0.5).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,328 @@
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::spatial::{SpatialEntry, SpatialHash};
+
+// which layers a collider is on and which layers it wants to touch. Two colliders only
+// interact when each one's filters include a layer the other is a member of
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CollisionLayers {
+    pub memberships: u32,
+    pub filters: u32,
+}
+
+impl CollisionLayers {
+    pub const fn new(memberships: u32, filters: u32) -> CollisionLayers {
+        CollisionLayers { memberships, filters }
+    }
+
+    pub fn interacts_with(&self, other: &CollisionLayers) -> bool {
+        self.filters & other.memberships != 0 && other.filters & self.memberships != 0
+    }
+}
+
+// shapes are centred on the entity and ignore its rotation
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ColliderShape {
+    Circle { radius: f32 },
+    Aabb { half_extents: Vec2 },
+    // upright: the straight part runs along y
+    Capsule { half_height: f32, radius: f32 },
+}
+
+impl ColliderShape {
+    // every shape is a box grown by a radius, which makes any pair a single test
+    fn core(&self) -> (Vec2, f32) {
+        match *self {
+            ColliderShape::Circle { radius } => (Vec2::ZERO, radius),
+            ColliderShape::Aabb { half_extents } => (half_extents, 0.0),
+            ColliderShape::Capsule { half_height, radius } => (Vec2::new(0.0, half_height), radius),
+        }
+    }
+
+    pub fn bounding_radius(&self) -> f32 {
+        let (half_extents, radius) = self.core();
+        half_extents.length() + radius
+    }
+
+    // touching counts as overlapping
+    pub fn overlaps(&self, position: Vec2, other: &ColliderShape, other_position: Vec2) -> bool {
+        let (half_extents, radius) = self.core();
+        let (other_half_extents, other_radius) = other.core();
+        let gap = ((other_position - position).abs() - half_extents - other_half_extents).max(Vec2::ZERO);
+        let reach = radius + other_radius;
+        gap.length_squared() <= reach * reach
+    }
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct Collider {
+    pub shape: ColliderShape,
+    pub layers: CollisionLayers,
+}
+
+impl Collider {
+    pub fn circle(radius: f32, layers: CollisionLayers) -> Collider {
+        Collider {
+            shape: ColliderShape::Circle { radius },
+            layers,
+        }
+    }
+
+    pub fn aabb(half_extents: Vec2, layers: CollisionLayers) -> Collider {
+        Collider {
+            shape: ColliderShape::Aabb { half_extents },
+            layers,
+        }
+    }
+
+    pub fn capsule(half_height: f32, radius: f32, layers: CollisionLayers) -> Collider {
+        Collider {
+            shape: ColliderShape::Capsule { half_height, radius },
+            layers,
+        }
+    }
+}
+
+// sent the tick two colliders start overlapping. The first entity is the one that comes
+// first in query order, so the order is reproducible but says nothing about which is which
+#[derive(Event, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CollisionStarted(pub Entity, pub Entity);
+
+// sent the tick they stop overlapping, or once either of them loses its Collider
+#[derive(Event, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CollisionEnded(pub Entity, pub Entity);
+
+impl CollisionStarted {
+    // the other entity, if `entity` is part of this collision
+    pub fn other(&self, entity: Entity) -> Option<Entity> {
+        pair_other((self.0, self.1), entity)
+    }
+}
+
+impl CollisionEnded {
+    pub fn other(&self, entity: Entity) -> Option<Entity> {
+        pair_other((self.0, self.1), entity)
+    }
+}
+
+fn pair_other((a, b): (Entity, Entity), entity: Entity) -> Option<Entity> {
+    if a == entity {
+        Some(b)
+    } else if b == entity {
+        Some(a)
+    } else {
+        None
+    }
+}
+
+// every pair overlapping as of the last detection, for systems that care about ongoing contact
+// rather than the moment it starts
+#[derive(Resource, Default)]
+pub struct Contacts {
+    pairs: Vec<(Entity, Entity)>,
+    lookup: HashSet<(Entity, Entity)>,
+}
+
+impl Contacts {
+    pub fn pairs(&self) -> &[(Entity, Entity)] {
+        &self.pairs
+    }
+
+    pub fn touching(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.pairs.iter().filter_map(move |pair| pair_other(*pair, entity))
+    }
+
+    pub fn contains(&self, a: Entity, b: Entity) -> bool {
+        self.lookup.contains(&(a, b)) || self.lookup.contains(&(b, a))
+    }
+
+    // forgets every contact without sending CollisionEnded, for when the whole world is reset
+    pub fn clear(&mut self) {
+        self.pairs.clear();
+        self.lookup.clear();
+    }
+}
+
+// the only place collision geometry is tested. Everything that reacts to a collision
+// reads the events or Contacts instead
+pub fn detect_collisions(
+    mut spatial_hash: ResMut<SpatialHash>,
+    mut contacts: ResMut<Contacts>,
+    collider_query: Query<(Entity, &Transform, &Collider)>,
+    mut started: EventWriter<CollisionStarted>,
+    mut ended: EventWriter<CollisionEnded>,
+) {
+    // pairs are ordered by query position rather than entity id: ids depend on what else was spawned
+    // (sounds, particles, ui), the query order only on the simulation itself
+    let colliders: Vec<(Entity, Vec2, Collider)> = collider_query
+        .iter()
+        .map(|(entity, transform, collider)| (entity, transform.translation.truncate(), *collider))
+        .collect();
+    spatial_hash.clear();
+    for (index, (_, position, collider)) in colliders.iter().enumerate() {
+        spatial_hash.insert(SpatialEntry {
+            index,
+            position: *position,
+            radius: collider.shape.bounding_radius(),
+        });
+    }
+
+    let mut indices = Vec::new();
+    for (index, (_, position, collider)) in colliders.iter().enumerate() {
+        for candidate in spatial_hash.query(*position, collider.shape.bounding_radius()) {
+            // each pair once
+            if candidate.index <= index {
+                continue;
+            }
+            let (_, other_position, other_collider) = &colliders[candidate.index];
+            if collider.layers.interacts_with(&other_collider.layers)
+                && collider.shape.overlaps(*position, &other_collider.shape, *other_position)
+            {
+                indices.push((index, candidate.index));
+            }
+        }
+    }
+    // the grid hands back candidates cell by cell, sorting keeps the events in query order
+    // however the entities happen to be bucketed
+    indices.sort_unstable();
+    let pairs: Vec<(Entity, Entity)> = indices.iter().map(|&(a, b)| (colliders[a].0, colliders[b].0)).collect();
+
+    let lookup: HashSet<(Entity, Entity)> = pairs.iter().copied().collect();
+    for (a, b) in contacts.pairs.iter() {
+        if !lookup.contains(&(*a, *b)) && !lookup.contains(&(*b, *a)) {
+            ended.send(CollisionEnded(*a, *b));
+        }
+    }
+    for (a, b) in pairs.iter() {
+        if !contacts.contains(*a, *b) {
+            started.send(CollisionStarted(*a, *b));
+        }
+    }
+    contacts.pairs = pairs;
+    contacts.lookup = lookup;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: u32 = 1 << 0;
+    const B: u32 = 1 << 1;
+
+    fn circle(radius: f32) -> ColliderShape {
+        ColliderShape::Circle { radius }
+    }
+
+    fn aabb(half_width: f32, half_height: f32) -> ColliderShape {
+        ColliderShape::Aabb { half_extents: Vec2::new(half_width, half_height) }
+    }
+
+    fn capsule(half_height: f32, radius: f32) -> ColliderShape {
+        ColliderShape::Capsule { half_height, radius }
+    }
+
+    #[test]
+    fn circles_overlap_up_to_touching() {
+        assert!(circle(1.0).overlaps(Vec2::ZERO, &circle(1.0), Vec2::new(1.5, 0.0)));
+        assert!(circle(1.0).overlaps(Vec2::ZERO, &circle(1.0), Vec2::new(2.0, 0.0)));
+        assert!(!circle(1.0).overlaps(Vec2::ZERO, &circle(1.0), Vec2::new(2.1, 0.0)));
+    }
+
+    #[test]
+    fn aabbs_overlap_on_both_axes() {
+        assert!(aabb(1.0, 1.0).overlaps(Vec2::ZERO, &aabb(1.0, 1.0), Vec2::new(1.9, 1.9)));
+        assert!(!aabb(1.0, 1.0).overlaps(Vec2::ZERO, &aabb(1.0, 1.0), Vec2::new(1.9, 2.1)));
+        assert!(!aabb(1.0, 1.0).overlaps(Vec2::ZERO, &aabb(1.0, 1.0), Vec2::new(2.1, 0.0)));
+    }
+
+    #[test]
+    fn circle_misses_the_corner_of_an_aabb() {
+        // within reach of both edges, but the corner is sqrt(2) * 0.8 away
+        let corner = Vec2::new(1.8, 1.8);
+        assert!(!circle(1.0).overlaps(corner, &aabb(1.0, 1.0), Vec2::ZERO));
+        assert!(circle(1.0).overlaps(Vec2::new(1.9, 0.0), &aabb(1.0, 1.0), Vec2::ZERO));
+        // the test is symmetric
+        assert!(!aabb(1.0, 1.0).overlaps(Vec2::ZERO, &circle(1.0), corner));
+    }
+
+    #[test]
+    fn capsule_reaches_further_along_y_than_x() {
+        let shape = capsule(2.0, 1.0);
+        assert!(shape.overlaps(Vec2::ZERO, &circle(0.5), Vec2::new(0.0, 3.4)));
+        assert!(!shape.overlaps(Vec2::ZERO, &circle(0.5), Vec2::new(0.0, 3.6)));
+        assert!(!shape.overlaps(Vec2::ZERO, &circle(0.5), Vec2::new(1.6, 0.0)));
+        assert!(shape.overlaps(Vec2::ZERO, &capsule(2.0, 1.0), Vec2::new(1.5, 4.5)));
+        assert!(!shape.overlaps(Vec2::ZERO, &capsule(2.0, 1.0), Vec2::new(1.9, 5.0)));
+    }
+
+    #[test]
+    fn layers_need_filters_on_both_sides() {
+        let player = CollisionLayers::new(A, B);
+        let enemy = CollisionLayers::new(B, A);
+        let deaf_enemy = CollisionLayers::new(B, 0);
+        assert!(player.interacts_with(&enemy));
+        assert!(enemy.interacts_with(&player));
+        assert!(!player.interacts_with(&deaf_enemy));
+        assert!(!player.interacts_with(&player));
+    }
+
+    fn collision_app() -> App {
+        let mut app = App::new();
+        app
+        .init_resource::<SpatialHash>()
+        .init_resource::<Contacts>()
+        .add_event::<CollisionStarted>()
+        .add_event::<CollisionEnded>()
+        .add_systems(Update, detect_collisions);
+        app
+    }
+
+    fn drain<E: Event>(app: &mut App) -> Vec<E> {
+        app.world.resource_mut::<Events<E>>().drain().collect()
+    }
+
+    #[test]
+    fn started_and_ended_are_sent_once_per_contact() {
+        let mut app = collision_app();
+        let layers = CollisionLayers::new(A, A);
+        let a = app.world.spawn((Transform::default(), Collider::circle(1.0, layers))).id();
+        let b = app.world.spawn((Transform::from_xyz(1.5, 0.0, 0.0), Collider::circle(1.0, layers))).id();
+
+        app.update();
+        assert_eq!(drain::<CollisionStarted>(&mut app), vec![CollisionStarted(a, b)]);
+        assert!(app.world.resource::<Contacts>().contains(b, a));
+
+        // still touching, nothing new to report
+        app.update();
+        assert!(drain::<CollisionStarted>(&mut app).is_empty());
+        assert!(drain::<CollisionEnded>(&mut app).is_empty());
+
+        app.world.get_mut::<Transform>(b).unwrap().translation.x = 10.0;
+        app.update();
+        assert_eq!(drain::<CollisionEnded>(&mut app), vec![CollisionEnded(a, b)]);
+        assert!(app.world.resource::<Contacts>().pairs().is_empty());
+    }
+
+    #[test]
+    fn losing_the_collider_ends_the_contact() {
+        let mut app = collision_app();
+        let layers = CollisionLayers::new(A, A);
+        let a = app.world.spawn((Transform::default(), Collider::circle(1.0, layers))).id();
+        let b = app.world.spawn((Transform::default(), Collider::circle(1.0, layers))).id();
+        app.update();
+        drain::<CollisionStarted>(&mut app);
+
+        app.world.entity_mut(b).remove::<Collider>();
+        app.update();
+        assert_eq!(drain::<CollisionEnded>(&mut app), vec![CollisionEnded(a, b)]);
+    }
+
+    #[test]
+    fn filtered_layers_never_collide() {
+        let mut app = collision_app();
+        app.world.spawn((Transform::default(), Collider::circle(1.0, CollisionLayers::new(A, B))));
+        app.world.spawn((Transform::default(), Collider::circle(1.0, CollisionLayers::new(A, B))));
+        app.update();
+        assert!(drain::<CollisionStarted>(&mut app).is_empty());
+    }
+}
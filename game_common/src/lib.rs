@@ -0,0 +1,39 @@
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use bevy::{prelude::*, window::WindowResized};
+
+pub mod arena;
+pub mod bindings;
+pub mod collision;
+pub mod movement;
+pub mod ron_file;
+pub mod score;
+pub mod settings;
+pub mod settings_menu;
+pub mod spatial;
+
+use arena::{center_camera_on_arena, follow_window_resize, spawn_camera, sync_arena_to_window, ArenaBounds, ArenaLocked};
+use collision::{CollisionEnded, CollisionStarted, Contacts};
+use spatial::SpatialHash;
+
+// the setup every game in the repo shares: an arena that follows the window, a camera centred on it
+// and the collision resources. The fixed step systems (apply_velocity, confine_to_arena,
+// detect_collisions) are left for each game to order within its own simulation
+pub struct GameCommonPlugin;
+
+impl Plugin for GameCommonPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<ArenaBounds>()
+        .init_resource::<ArenaLocked>()
+        .init_resource::<SpatialHash>()
+        .init_resource::<Contacts>()
+        // registered here too so a headless app, which has no window plugin, can still run
+        .add_event::<WindowResized>()
+        .add_event::<CollisionStarted>()
+        .add_event::<CollisionEnded>()
+        .add_systems(PreStartup, sync_arena_to_window)
+        .add_systems(Startup, spawn_camera)
+        .add_systems(PreUpdate, (follow_window_resize, center_camera_on_arena).chain());
+    }
+}
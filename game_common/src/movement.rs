@@ -0,0 +1,15 @@
+use bevy::prelude::*;
+
+// units per second. Games set it from their own steering, apply_velocity does the moving
+#[derive(Component, Clone, Copy, PartialEq, Debug, Default)]
+pub struct Velocity(pub Vec2);
+
+pub fn apply_velocity(
+    fixed_time: Res<FixedTime>,
+    mut moving_query: Query<(&mut Transform, &Velocity)>,
+) {
+    let delta = fixed_time.period.as_secs_f32();
+    for (mut transform, velocity) in moving_query.iter_mut() {
+        transform.translation += velocity.0.extend(0.0) * delta;
+    }
+}
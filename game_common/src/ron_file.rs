@@ -0,0 +1,139 @@
+use std::{fmt, fs, io, path::Path};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+// the files the games keep in the player's config and data dirs (settings, bindings, high scores)
+// all go through here, so they are read, written and reported the same way
+
+#[derive(Debug)]
+pub enum RonFileError {
+    Io(io::Error),
+    // the file is there but isn't valid for the type it is read as
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+}
+
+impl fmt::Display for RonFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RonFileError::Io(err) => write!(f, "{}", err),
+            RonFileError::Parse(err) => write!(f, "{}", err),
+            RonFileError::Serialize(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+// None when there is no file yet
+pub fn read_ron_file<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, RonFileError> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(RonFileError::Io(err)),
+    };
+    ron::from_str::<T>(&contents).map(Some).map_err(RonFileError::Parse)
+}
+
+// pretty printed so the file is easy to edit by hand, creating its folder first
+pub fn write_ron_file<T: Serialize>(path: &Path, value: &T) -> Result<(), RonFileError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(RonFileError::Io)?;
+    }
+    let contents = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(RonFileError::Serialize)?;
+    fs::write(path, contents).map_err(RonFileError::Io)
+}
+
+// replaces `value` with the file's contents after passing them through `validated`.
+// with no file yet `value` is written out so there is one to edit. A file that can't be read is
+// left alone so the player can fix it, and `value` keeps its defaults meanwhile
+pub fn load_or_create_ron_file<T: Serialize + DeserializeOwned>(
+    path: &Path,
+    what: &str,
+    value: &mut T,
+    validated: impl FnOnce(T) -> T,
+) {
+    match read_ron_file::<T>(path) {
+        Ok(Some(loaded)) => *value = validated(loaded),
+        Ok(None) => {
+            if let Err(err) = write_ron_file(path, value) {
+                warn!("could not write default {} to {:?}: {}", what, path, err);
+            }
+        }
+        Err(err) => warn!("could not load {} from {:?}, using defaults: {}", what, path, err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Default)]
+    struct Sample {
+        name: String,
+        value: u32,
+    }
+
+    // a fresh path per test so they can run in parallel
+    fn temp_path(test: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("game_common_ron_file_{}_{}", test, std::process::id()))
+            .join("sample.ron")
+    }
+
+    #[test]
+    fn missing_file_reads_as_none() {
+        let path = temp_path("missing");
+        assert!(matches!(read_ron_file::<Sample>(&path), Ok(None)));
+    }
+
+    #[test]
+    fn written_file_reads_back() {
+        let path = temp_path("round_trip");
+        let sample = Sample { name: "star".to_string(), value: 7 };
+        write_ron_file(&path, &sample).unwrap();
+        assert_eq!(read_ron_file::<Sample>(&path).unwrap(), Some(sample));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn broken_file_is_a_parse_error() {
+        let path = temp_path("broken");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "(name: ").unwrap();
+        assert!(matches!(read_ron_file::<Sample>(&path), Err(RonFileError::Parse(_))));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn load_writes_defaults_when_missing() {
+        let path = temp_path("defaults");
+        let mut sample = Sample { name: "default".to_string(), value: 1 };
+        load_or_create_ron_file(&path, "sample", &mut sample, |loaded| loaded);
+        assert_eq!(read_ron_file::<Sample>(&path).unwrap(), Some(sample));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn load_validates_and_leaves_broken_files_alone() {
+        let path = temp_path("validate");
+        write_ron_file(&path, &Sample { name: "big".to_string(), value: 500 }).unwrap();
+        let mut sample = Sample::default();
+        load_or_create_ron_file(&path, "sample", &mut sample, |mut loaded| {
+            loaded.value = loaded.value.min(100);
+            loaded
+        });
+        assert_eq!(sample, Sample { name: "big".to_string(), value: 100 });
+
+        fs::write(&path, "not ron").unwrap();
+        let mut sample = Sample::default();
+        load_or_create_ron_file(&path, "sample", &mut sample, |loaded| loaded);
+        assert_eq!(sample, Sample::default());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "not ron");
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
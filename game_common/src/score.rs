@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+
+// points for a single player game
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Score {
+    pub value: u32,
+}
+
+// one score per side, for two player games
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct PlayerScores {
+    pub player_one: Score,
+    pub player_two: Score,
+}
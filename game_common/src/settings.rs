@@ -0,0 +1,196 @@
+use std::path::PathBuf;
+
+use bevy::{prelude::*, window::{PresentMode, PrimaryWindow, WindowMode}};
+use serde::{Deserialize, Serialize};
+
+use crate::ron_file::load_or_create_ron_file;
+
+// the sizes the settings screen steps through, the file itself may hold any size
+pub const RESOLUTIONS: [(u32, u32); 5] = [(1280, 720), (1366, 768), (1600, 900), (1920, 1080), (2560, 1440)];
+pub const MIN_RESOLUTION: (u32, u32) = (640, 360);
+
+// loads the player's settings from their config dir, writing the defaults out the first time,
+// and keeps the primary window in line with them
+pub struct SettingsPlugin {
+    // folder under the platform config dir the file goes in
+    pub app_dir: &'static str,
+    // set this env var to use a settings file somewhere else
+    pub path_env: &'static str,
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<Settings>()
+        .insert_resource(SettingsPath::new(self.app_dir, self.path_env))
+        .add_systems(PreStartup, load_settings)
+        .add_systems(Update, apply_window_settings);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 3] = [DisplayMode::Windowed, DisplayMode::Borderless, DisplayMode::Fullscreen];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DisplayMode::Windowed => "Windowed",
+            DisplayMode::Borderless => "Borderless",
+            DisplayMode::Fullscreen => "Fullscreen",
+        }
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+            DisplayMode::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+// what the player picked on the settings screen. Any field left out of the file keeps its default
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Settings {
+    pub display_mode: DisplayMode,
+    // width and height of the window while windowed
+    pub resolution: (u32, u32),
+    pub vsync: bool,
+    // both 0.0 to 1.0, the sound effects are scaled by the master volume
+    pub master_volume: f32,
+    pub sfx_volume: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            display_mode: DisplayMode::Windowed,
+            resolution: RESOLUTIONS[0],
+            vsync: true,
+            master_volume: 1.0,
+            sfx_volume: 1.0,
+        }
+    }
+}
+
+impl Settings {
+    // pulls values a hand edited file got wrong back into range
+    pub fn validated(mut self) -> Settings {
+        let (width, height) = self.resolution;
+        if width < MIN_RESOLUTION.0 || height < MIN_RESOLUTION.1 {
+            warn!("resolution {}x{} is below {}x{}, raising it", width, height, MIN_RESOLUTION.0, MIN_RESOLUTION.1);
+            self.resolution = (width.max(MIN_RESOLUTION.0), height.max(MIN_RESOLUTION.1));
+        }
+        for (name, volume) in [("master_volume", &mut self.master_volume), ("sfx_volume", &mut self.sfx_volume)] {
+            // clamping leaves NaN as it is, so anything that isn't a number goes back to full volume
+            if !volume.is_finite() {
+                warn!("{} {} is not a volume, resetting it", name, volume);
+                *volume = 1.0;
+            } else if !(0.0..=1.0).contains(&*volume) {
+                warn!("{} {} is outside 0.0..=1.0, clamping it", name, volume);
+                *volume = volume.clamp(0.0, 1.0);
+            }
+        }
+        self
+    }
+}
+
+#[derive(Resource)]
+pub struct SettingsPath {
+    pub path: PathBuf,
+}
+
+impl SettingsPath {
+    pub fn new(app_dir: &str, path_env: &str) -> SettingsPath {
+        let path = match std::env::var_os(path_env) {
+            Some(path) => PathBuf::from(path),
+            None => dirs::config_dir()
+                .map(|dir| dir.join(app_dir))
+                .unwrap_or_default()
+                .join("settings.ron"),
+        };
+        SettingsPath { path }
+    }
+}
+
+pub fn load_settings(
+    settings_path: Res<SettingsPath>,
+    mut settings: ResMut<Settings>,
+) {
+    load_or_create_ron_file(&settings_path.path, "settings", &mut *settings, Settings::validated);
+}
+
+// runs once after loading and again whenever the settings screen changes something.
+// resizing the window moves the arena along with it through WindowResized
+pub fn apply_window_settings(
+    settings: Res<Settings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let (width, height) = settings.resolution;
+    for mut window in window_query.iter_mut() {
+        window.mode = settings.display_mode.window_mode();
+        window.resolution.set(width as f32, height as f32);
+        window.present_mode = if settings.vsync { PresentMode::AutoVsync } else { PresentMode::AutoNoVsync };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validated_keeps_good_settings() {
+        let settings = Settings {
+            display_mode: DisplayMode::Borderless,
+            resolution: (1920, 1080),
+            vsync: false,
+            master_volume: 0.5,
+            sfx_volume: 0.0,
+        };
+        assert_eq!(settings.clone().validated(), settings);
+    }
+
+    #[test]
+    fn validated_raises_small_resolutions() {
+        let settings = Settings {
+            resolution: (320, 1080),
+            ..default()
+        };
+        assert_eq!(settings.validated().resolution, (MIN_RESOLUTION.0, 1080));
+    }
+
+    #[test]
+    fn validated_clamps_volumes() {
+        let settings = Settings {
+            master_volume: 1.5,
+            sfx_volume: -0.2,
+            ..default()
+        };
+        let settings = settings.validated();
+        assert_eq!(settings.master_volume, 1.0);
+        assert_eq!(settings.sfx_volume, 0.0);
+    }
+
+    #[test]
+    fn validated_resets_volumes_that_are_not_numbers() {
+        let settings = Settings {
+            master_volume: f32::NAN,
+            sfx_volume: f32::INFINITY,
+            ..default()
+        };
+        let settings = settings.validated();
+        assert_eq!(settings.master_volume, 1.0);
+        assert_eq!(settings.sfx_volume, 1.0);
+    }
+}
@@ -0,0 +1,349 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+use crate::ron_file::write_ron_file;
+use crate::settings::{DisplayMode, Settings, SettingsPath, RESOLUTIONS};
+
+pub const SETTINGS_MENU_FONT_SIZE: f32 = 32.0;
+pub const SETTINGS_MENU_BACKDROP: Color = Color::rgba(0.0, 0.0, 0.0, 0.85);
+pub const SETTINGS_MENU_SELECTED: Color = Color::rgb(1.0, 0.85, 0.3);
+pub const VOLUME_STEP: f32 = 0.1;
+
+// the settings screen both games open on top of whatever they are showing. `B` is the game's
+// input bindings, so its keys can be rebound here too. Changes apply straight away and are
+// written to the settings file when the screen closes.
+// the screen reads the keyboard and d-pad directly rather than through the game's actions,
+// so a bad rebind can't lock the player out of the menu that fixes it
+pub struct SettingsMenuPlugin<B> {
+    marker: PhantomData<fn() -> B>,
+}
+
+impl<B> Default for SettingsMenuPlugin<B> {
+    fn default() -> SettingsMenuPlugin<B> {
+        SettingsMenuPlugin { marker: PhantomData }
+    }
+}
+
+impl<B: RebindKeys> Plugin for SettingsMenuPlugin<B> {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<SettingsMenu>()
+        .add_event::<OpenSettings>()
+        .add_event::<SettingsClosed>()
+        // after Update, so a key that closes the screen is gone by the time the game reads its actions again
+        .add_systems(PostUpdate, (navigate_settings_menu::<B>, open_settings_menu, sync_settings_screen::<B>).chain());
+    }
+}
+
+// implemented by a game's input bindings so the settings screen can list and rebind keys
+// without knowing which actions the game has
+pub trait RebindKeys: Resource {
+    // (action name, bound keys), in the order the rows are shown
+    fn key_rows(&self) -> Vec<(&'static str, Vec<KeyCode>)>;
+    // makes `key` the first key of the action in that row, without leaving it bound to two actions
+    fn rebind(&mut self, row: usize, key: KeyCode);
+}
+
+// sent by the game to bring the settings screen up
+#[derive(Event, Clone, Copy, Debug)]
+pub struct OpenSettings;
+
+// sent once the screen is gone and the settings file written, games save their bindings on it
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SettingsClosed;
+
+#[derive(Resource, Default)]
+pub struct SettingsMenu {
+    pub open: bool,
+    // index into the rows from `settings_rows`
+    pub selected: usize,
+    // the key row waiting for the next key press
+    pub rebinding: Option<usize>,
+}
+
+// run condition for game input that would fight with the settings screen
+pub fn settings_menu_closed(menu: Option<Res<SettingsMenu>>) -> bool {
+    menu.is_none_or(|menu| !menu.open)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SettingsRow {
+    DisplayMode,
+    Resolution,
+    VSync,
+    MasterVolume,
+    SfxVolume,
+    // index into `RebindKeys::key_rows`
+    Key(usize),
+    Back,
+}
+
+pub fn settings_rows(key_rows: usize) -> Vec<SettingsRow> {
+    let mut rows = vec![
+        SettingsRow::DisplayMode,
+        SettingsRow::Resolution,
+        SettingsRow::VSync,
+        SettingsRow::MasterVolume,
+        SettingsRow::SfxVolume,
+    ];
+    rows.extend((0..key_rows).map(SettingsRow::Key));
+    rows.push(SettingsRow::Back);
+    rows
+}
+
+// one frame of menu input from the arrow keys and every connected gamepad's d-pad
+#[derive(Default, Clone, Copy, Debug)]
+pub struct MenuInput {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub confirm: bool,
+    pub back: bool,
+}
+
+impl MenuInput {
+    pub fn read(keyboard: &Input<KeyCode>, gamepads: &Gamepads, gamepad_buttons: &Input<GamepadButton>) -> MenuInput {
+        let button = |button_type| gamepads.iter().any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)));
+        MenuInput {
+            up: keyboard.just_pressed(KeyCode::Up) || button(GamepadButtonType::DPadUp),
+            down: keyboard.just_pressed(KeyCode::Down) || button(GamepadButtonType::DPadDown),
+            left: keyboard.just_pressed(KeyCode::Left) || button(GamepadButtonType::DPadLeft),
+            right: keyboard.just_pressed(KeyCode::Right) || button(GamepadButtonType::DPadRight),
+            confirm: keyboard.any_just_pressed([KeyCode::Return, KeyCode::Space]) || button(GamepadButtonType::South),
+            back: keyboard.just_pressed(KeyCode::Escape) || button(GamepadButtonType::East),
+        }
+    }
+
+    // -1, 0 or 1 for left/right
+    pub fn step(&self) -> i32 {
+        self.right as i32 - self.left as i32
+    }
+}
+
+// moves `current` by `step` places through `options`, wrapping around. A value that isn't one of
+// the options (e.g. a resolution typed into the file) starts from the first one
+fn cycle<T: Copy + PartialEq>(options: &[T], current: T, step: i32) -> T {
+    let index = options.iter().position(|option| *option == current).unwrap_or(0) as i32;
+    options[(index + step).rem_euclid(options.len() as i32) as usize]
+}
+
+fn step_volume(volume: f32, step: i32) -> f32 {
+    // rounded so repeated steps land back on exact tenths
+    ((volume + step as f32 * VOLUME_STEP).clamp(0.0, 1.0) * 10.0).round() / 10.0
+}
+
+pub fn open_settings_menu(mut open_events: EventReader<OpenSettings>, mut menu: ResMut<SettingsMenu>) {
+    if open_events.iter().count() > 0 && !menu.open {
+        *menu = SettingsMenu {
+            open: true,
+            ..default()
+        };
+    }
+}
+
+pub fn navigate_settings_menu<B: RebindKeys>(
+    keyboard: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    settings_path: Res<SettingsPath>,
+    mut menu: ResMut<SettingsMenu>,
+    mut settings: ResMut<Settings>,
+    mut bindings: ResMut<B>,
+    mut closed: EventWriter<SettingsClosed>,
+) {
+    if !menu.open {
+        return;
+    }
+    let input = MenuInput::read(&keyboard, &gamepads, &gamepad_buttons);
+    if let Some(row) = menu.rebinding {
+        // backing out cancels the rebind instead of binding escape
+        if input.back {
+            menu.rebinding = None;
+        } else if let Some(&key) = keyboard.get_just_pressed().next() {
+            bindings.rebind(row, key);
+            menu.rebinding = None;
+        }
+        return;
+    }
+
+    let rows = settings_rows(bindings.key_rows().len());
+    if input.up {
+        menu.selected = (menu.selected + rows.len() - 1) % rows.len();
+    }
+    if input.down {
+        menu.selected = (menu.selected + 1) % rows.len();
+    }
+
+    let row = rows[menu.selected.min(rows.len() - 1)];
+    let step = input.step();
+    match row {
+        SettingsRow::DisplayMode if step != 0 => {
+            settings.display_mode = cycle(&DisplayMode::ALL, settings.display_mode, step);
+        }
+        SettingsRow::Resolution if step != 0 => {
+            settings.resolution = cycle(&RESOLUTIONS, settings.resolution, step);
+        }
+        SettingsRow::VSync if step != 0 || input.confirm => {
+            settings.vsync = !settings.vsync;
+        }
+        SettingsRow::MasterVolume if step != 0 => {
+            settings.master_volume = step_volume(settings.master_volume, step);
+        }
+        SettingsRow::SfxVolume if step != 0 => {
+            settings.sfx_volume = step_volume(settings.sfx_volume, step);
+        }
+        SettingsRow::Key(key_row) if input.confirm => {
+            menu.rebinding = Some(key_row);
+        }
+        _ => {}
+    }
+
+    if input.back || (input.confirm && row == SettingsRow::Back) {
+        menu.open = false;
+        if let Err(err) = write_ron_file(&settings_path.path, &*settings) {
+            warn!("could not save settings to {:?}: {}", settings_path.path, err);
+        }
+        closed.send(SettingsClosed);
+    }
+}
+
+#[derive(Component)]
+pub struct SettingsScreen{}
+
+// the text of one row, by index into `settings_rows`
+#[derive(Component)]
+pub struct SettingsRowText(pub usize);
+
+fn row_text(row: SettingsRow, settings: &Settings, key_rows: &[(&'static str, Vec<KeyCode>)], rebinding: bool) -> String {
+    match row {
+        SettingsRow::DisplayMode => format!("Display: < {} >", settings.display_mode.label()),
+        SettingsRow::Resolution => format!("Resolution: < {}x{} >", settings.resolution.0, settings.resolution.1),
+        SettingsRow::VSync => format!("VSync: {}", if settings.vsync { "On" } else { "Off" }),
+        SettingsRow::MasterVolume => format!("Master volume: < {:.0}% >", settings.master_volume * 100.0),
+        SettingsRow::SfxVolume => format!("Effects volume: < {:.0}% >", settings.sfx_volume * 100.0),
+        SettingsRow::Key(_) if rebinding => "Press a key (Esc to cancel)".to_string(),
+        SettingsRow::Key(index) => {
+            let (name, keys) = &key_rows[index];
+            let keys: Vec<String> = keys.iter().map(|key| format!("{:?}", key)).collect();
+            format!("{}: {}", name, keys.join(", "))
+        }
+        SettingsRow::Back => "Back".to_string(),
+    }
+}
+
+fn row_style(index: usize, row: SettingsRow, menu: &SettingsMenu, settings: &Settings, key_rows: &[(&'static str, Vec<KeyCode>)]) -> (String, Color) {
+    let rebinding = matches!(row, SettingsRow::Key(key_row) if menu.rebinding == Some(key_row));
+    let color = if index == menu.selected { SETTINGS_MENU_SELECTED } else { Color::WHITE };
+    (row_text(row, settings, key_rows, rebinding), color)
+}
+
+// spawns the screen when the menu opens, keeps its rows up to date and removes it on close
+pub fn sync_settings_screen<B: RebindKeys>(
+    mut commands: Commands,
+    menu: Res<SettingsMenu>,
+    settings: Res<Settings>,
+    bindings: Res<B>,
+    screen_query: Query<Entity, With<SettingsScreen>>,
+    mut text_query: Query<(&SettingsRowText, &mut Text)>,
+) {
+    if !menu.open {
+        for entity in screen_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    let key_rows = bindings.key_rows();
+    let rows = settings_rows(key_rows.len());
+    if screen_query.is_empty() {
+        commands.spawn(
+            (
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(6.0),
+                        ..default()
+                    },
+                    background_color: SETTINGS_MENU_BACKDROP.into(),
+                    // above the game's own menus
+                    z_index: ZIndex::Global(10),
+                    ..default()
+                },
+                SettingsScreen {},
+            )
+        ).with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "Settings",
+                    TextStyle {
+                        font_size: 48.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                )
+            );
+            for (index, &row) in rows.iter().enumerate() {
+                let (value, color) = row_style(index, row, &menu, &settings, &key_rows);
+                parent.spawn((
+                    TextBundle::from_section(
+                        value,
+                        TextStyle {
+                            font_size: SETTINGS_MENU_FONT_SIZE,
+                            color,
+                            ..default()
+                        },
+                    ),
+                    SettingsRowText(index),
+                ));
+            }
+        });
+        return;
+    }
+
+    if !(menu.is_changed() || settings.is_changed() || bindings.is_changed()) {
+        return;
+    }
+    for (row_index, mut text) in text_query.iter_mut() {
+        let Some(&row) = rows.get(row_index.0) else {
+            continue;
+        };
+        let (value, color) = row_style(row_index.0, row, &menu, &settings, &key_rows);
+        text.sections[0].value = value;
+        text.sections[0].style.color = color;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycle_wraps_both_ways() {
+        assert_eq!(cycle(&DisplayMode::ALL, DisplayMode::Fullscreen, 1), DisplayMode::Windowed);
+        assert_eq!(cycle(&DisplayMode::ALL, DisplayMode::Windowed, -1), DisplayMode::Fullscreen);
+        assert_eq!(cycle(&DisplayMode::ALL, DisplayMode::Windowed, 1), DisplayMode::Borderless);
+    }
+
+    #[test]
+    fn cycle_starts_unknown_values_from_the_first_option() {
+        assert_eq!(cycle(&RESOLUTIONS, (1000, 700), 1), RESOLUTIONS[1]);
+    }
+
+    #[test]
+    fn step_volume_lands_on_tenths_and_stays_in_range() {
+        let mut volume = 0.0;
+        for _ in 0..3 {
+            volume = step_volume(volume, 1);
+        }
+        assert_eq!(volume, 0.3);
+        assert_eq!(step_volume(1.0, 1), 1.0);
+        assert_eq!(step_volume(0.05, -1), 0.0);
+    }
+}
@@ -0,0 +1,123 @@
+use bevy::{prelude::*, utils::HashMap};
+
+pub const SPATIAL_CELL_SIZE: f32 = 128.0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SpatialEntry {
+    // position of the entry in whatever list the hash was built from
+    pub index: usize,
+    pub position: Vec2,
+    pub radius: f32,
+}
+
+// uniform grid of bounding circles, rebuilt every tick. Entries are bucketed by their centre,
+// so lookups widen the search by the largest radius inserted
+#[derive(Resource)]
+pub struct SpatialHash {
+    cell_size: f32,
+    max_radius: f32,
+    cells: HashMap<(i32, i32), Vec<SpatialEntry>>,
+}
+
+impl Default for SpatialHash {
+    fn default() -> SpatialHash {
+        SpatialHash::new(SPATIAL_CELL_SIZE)
+    }
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32) -> SpatialHash {
+        SpatialHash {
+            cell_size,
+            max_radius: 0.0,
+            cells: HashMap::default(),
+        }
+    }
+
+    fn cell(&self, position: Vec2) -> (i32, i32) {
+        (
+            (position.x / self.cell_size).floor() as i32,
+            (position.y / self.cell_size).floor() as i32,
+        )
+    }
+
+    // empties every cell but keeps their allocations for the next rebuild
+    pub fn clear(&mut self) {
+        for entries in self.cells.values_mut() {
+            entries.clear();
+        }
+        self.max_radius = 0.0;
+    }
+
+    pub fn insert(&mut self, entry: SpatialEntry) {
+        self.max_radius = self.max_radius.max(entry.radius);
+        let cell = self.cell(entry.position);
+        self.cells.entry(cell).or_default().push(entry);
+    }
+
+    // every entry whose circle overlaps the given one, visited in a stable order
+    pub fn query(&self, position: Vec2, radius: f32) -> impl Iterator<Item = &SpatialEntry> + '_ {
+        let reach = radius + self.max_radius;
+        let (min_x, min_y) = self.cell(position - Vec2::splat(reach));
+        let (max_x, max_y) = self.cell(position + Vec2::splat(reach));
+        (min_y..=max_y)
+            .flat_map(move |y| (min_x..=max_x).map(move |x| (x, y)))
+            .filter_map(move |cell| self.cells.get(&cell))
+            .flat_map(|entries| entries.iter())
+            .filter(move |entry| entry.position.distance(position) <= radius + entry.radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(index: usize, x: f32, y: f32, radius: f32) -> SpatialEntry {
+        SpatialEntry {
+            index,
+            position: Vec2::new(x, y),
+            radius,
+        }
+    }
+
+    fn found(hash: &SpatialHash, position: Vec2, radius: f32) -> Vec<usize> {
+        let mut indices: Vec<usize> = hash.query(position, radius).map(|entry| entry.index).collect();
+        indices.sort_unstable();
+        indices
+    }
+
+    #[test]
+    fn query_finds_overlapping_circles_only() {
+        let mut hash = SpatialHash::new(10.0);
+        hash.insert(entry(0, 0.0, 0.0, 1.0));
+        hash.insert(entry(1, 3.0, 0.0, 1.0));
+        hash.insert(entry(2, 50.0, 50.0, 1.0));
+        assert_eq!(found(&hash, Vec2::new(1.5, 0.0), 1.0), vec![0, 1]);
+        assert_eq!(found(&hash, Vec2::new(50.0, 48.5), 1.0), vec![2]);
+        assert!(found(&hash, Vec2::new(25.0, 25.0), 1.0).is_empty());
+    }
+
+    #[test]
+    fn query_reaches_across_cells_and_negative_coordinates() {
+        let mut hash = SpatialHash::new(10.0);
+        hash.insert(entry(0, -0.5, -0.5, 1.0));
+        hash.insert(entry(1, 9.9, 0.0, 1.0));
+        assert_eq!(found(&hash, Vec2::new(0.5, 0.0), 1.0), vec![0]);
+        assert_eq!(found(&hash, Vec2::new(10.5, 0.0), 0.5), vec![1]);
+    }
+
+    #[test]
+    fn query_finds_big_entries_centred_in_far_cells() {
+        let mut hash = SpatialHash::new(10.0);
+        hash.insert(entry(0, 40.0, 0.0, 35.0));
+        assert_eq!(found(&hash, Vec2::new(4.0, 0.0), 1.0), vec![0]);
+    }
+
+    #[test]
+    fn clear_empties_the_hash() {
+        let mut hash = SpatialHash::new(10.0);
+        hash.insert(entry(0, 0.0, 0.0, 1.0));
+        hash.clear();
+        assert!(found(&hash, Vec2::ZERO, 1.0).is_empty());
+    }
+}
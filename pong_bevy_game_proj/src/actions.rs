@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use bevy::{input::InputSystem, prelude::*};
+use game_common::bindings::{apply_deadzone, Binding, GameAction};
+use serde::{Deserialize, Serialize};
+
+// set this to use a bindings file somewhere other than the platform config dir
+pub const BINDINGS_PATH_ENV: &str = "PONG_BINDINGS";
+
+pub type InputBindings = game_common::bindings::InputBindings<Action>;
+
+// turns raw keyboard and gamepad state into `Input<Action>` and `MoveAxis` every frame
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<InputBindings>()
+        .init_resource::<Input<Action>>()
+        .init_resource::<MoveAxis>()
+        .add_systems(PreUpdate, update_actions.after(InputSystem));
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    // opens the settings screen, the rally waits while it is up
+    Settings,
+    Quit,
+}
+
+impl GameAction for Action {
+    fn default_bindings() -> BTreeMap<Action, Binding> {
+        BTreeMap::from([
+            (Action::MoveUp, Binding::new(&[KeyCode::W], &[GamepadButtonType::DPadUp])),
+            (Action::MoveDown, Binding::new(&[KeyCode::S], &[GamepadButtonType::DPadDown])),
+            (Action::Settings, Binding::new(&[KeyCode::Tab], &[GamepadButtonType::Start])),
+            (Action::Quit, Binding::new(&[KeyCode::Escape], &[GamepadButtonType::Select])),
+        ])
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::Settings => "Settings",
+            Action::Quit => "Quit",
+        }
+    }
+}
+
+// vertical paddle movement from the bound keys, d-pad and left stick, anywhere from -1 to 1
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct MoveAxis {
+    pub value: f32,
+}
+
+pub fn update_actions(
+    bindings: Res<InputBindings>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut actions: ResMut<Input<Action>>,
+    mut move_axis: ResMut<MoveAxis>,
+) {
+    bindings.update_actions(&keyboard_input, &gamepads, &gamepad_buttons, &mut actions);
+
+    let mut digital: f32 = 0.0;
+    if actions.pressed(Action::MoveUp) {
+        digital += 1.0;
+    }
+    if actions.pressed(Action::MoveDown) {
+        digital -= 1.0;
+    }
+
+    let mut analog: f32 = 0.0;
+    for gamepad in gamepads.iter() {
+        let stick = gamepad_axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY)).unwrap_or(0.0);
+        let stick = apply_deadzone(Vec2::new(0.0, stick), bindings.stick_deadzone).y;
+        if stick.abs() > analog.abs() {
+            analog = stick;
+        }
+    }
+
+    move_axis.value = if analog.abs() > digital.abs() { analog } else { digital };
+}
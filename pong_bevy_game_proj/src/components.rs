@@ -0,0 +1,17 @@
+use bevy::prelude::*;
+use game_common::collision::CollisionLayers;
+
+// collision layers, the star only ever bounces off the paddles
+pub const PADDLE_LAYER: u32 = 1 << 0;
+pub const STAR_LAYER: u32 = 1 << 1;
+pub const PADDLE_COLLISION: CollisionLayers = CollisionLayers::new(PADDLE_LAYER, STAR_LAYER);
+pub const STAR_COLLISION: CollisionLayers = CollisionLayers::new(STAR_LAYER, PADDLE_LAYER);
+
+#[derive(Component)]
+pub struct PlayerOne{}
+
+#[derive(Component)]
+pub struct PlayerTwo{}
+
+#[derive(Component)]
+pub struct Star{}
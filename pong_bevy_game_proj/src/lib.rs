@@ -0,0 +1,72 @@
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use std::time::Duration;
+
+use bevy::{input::InputPlugin, prelude::*, time::TimeUpdateStrategy};
+use game_common::{
+    arena::{confine_to_arena, ArenaBounds},
+    collision::detect_collisions,
+    movement::apply_velocity,
+    score::PlayerScores,
+    settings_menu::{settings_menu_closed, OpenSettings},
+    GameCommonPlugin,
+};
+
+pub mod actions;
+pub mod components;
+pub mod events;
+pub mod resources;
+pub mod systems;
+
+use actions::ActionsPlugin;
+use resources::*;
+use systems::*;
+
+pub const PLAYER_SIZE: f32 = 64.0;
+pub const PLAYER_WIDTH: f32 = 10.0;
+pub const STAR_SIZE: f32 = 30.0;
+pub const PLAYER_SPEED: f32 = 600.0;
+pub const STAR_SPEED: f32 = 500.0;
+pub const STAR_ROTATE_SPEED: f32 = 5.0;
+pub const FIXED_TIMESTEP: f32 = 1.0 / 60.0;
+// set this to use a settings file somewhere other than the platform config dir
+pub const SETTINGS_PATH_ENV: &str = "PONG_SETTINGS";
+
+// the pong simulation, without any window or rendering plugins
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .add_plugins((GameCommonPlugin, ActionsPlugin))
+        .init_resource::<PlayerScores>()
+        .init_resource::<SimulationRng>()
+        .insert_resource(FixedTime::new_from_secs(FIXED_TIMESTEP))
+        // registered here too so the headless app, which has no settings screen, can still run
+        .add_event::<OpenSettings>()
+        .add_systems(PreStartup, load_game_assets)
+        .add_systems(Startup, (spawn_player_one, spawn_player_two, spawn_star))
+        // fixed step and fixed order so a seed always plays out the same rally.
+        // it holds still while the settings screen is up
+        .add_systems(FixedUpdate, (player_one_movement,
+            apply_velocity,
+            spin_star,
+            player_two_movement,
+            confine_to_arena,
+            change_star_direction,
+            detect_collisions,
+            star_paddle_collision).chain().run_if(settings_menu_closed))
+        .add_systems(Update, (open_settings, exit_game).run_if(settings_menu_closed));
+    }
+}
+
+// builds an app that runs without a window, gpu or asset folder.
+// every call to `app.update()` advances the simulation by exactly one FIXED_TIMESTEP tick
+pub fn headless_app(arena: ArenaBounds) -> App {
+    let mut app = App::new();
+    app
+    .add_plugins((MinimalPlugins, InputPlugin, GamePlugin))
+    .insert_resource(arena)
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(FIXED_TIMESTEP)));
+    app
+}
@@ -1,25 +1,58 @@
 use bevy::prelude::*;
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
 
-#[derive(Resource)]
-pub struct PlayerOneScore{
-    pub value: u32,
+// left as default handles when there is no AssetServer
+#[derive(Resource, Default)]
+pub struct GameAssets {
+    pub player_texture: Handle<Image>,
+    pub star_texture: Handle<Image>,
 }
 
+// set this to serve the star the same way every time
+pub const SEED_ENV: &str = "PONG_SEED";
+
+// the only source of randomness the simulation is allowed to use
 #[derive(Resource)]
-pub struct PlayerTwoScore{
-    pub value: u32,
+pub struct SimulationRng {
+    pub seed: u64,
+    // ChaCha8 so a seed still serves the same way after a `cargo update`
+    pub generator: ChaCha8Rng,
 }
 
-impl Default for PlayerOneScore {
-    fn default() -> PlayerOneScore {
-        PlayerOneScore {
-            value: 0
+impl SimulationRng {
+    pub fn new(seed: u64) -> SimulationRng {
+        SimulationRng {
+            seed,
+            generator: ChaCha8Rng::seed_from_u64(seed),
         }
     }
 }
 
-impl Default for PlayerTwoScore {
-    fn default() -> PlayerTwoScore {
-        PlayerTwoScore {value: 0}
+impl Default for SimulationRng {
+    fn default() -> SimulationRng {
+        let seed = std::env::var(SEED_ENV)
+            .ok()
+            .and_then(|seed| seed.parse::<u64>().ok())
+            .unwrap_or_else(random::<u64>);
+        SimulationRng::new(seed)
+    }
+}
+
+impl RngCore for SimulationRng {
+    fn next_u32(&mut self) -> u32 {
+        self.generator.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.generator.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.generator.fill_bytes(dest)
     }
-}
\ No newline at end of file
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.generator.try_fill_bytes(dest)
+    }
+}
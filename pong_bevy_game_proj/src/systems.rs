@@ -0,0 +1,178 @@
+use bevy::{app::AppExit, prelude::*};
+use game_common::{
+    arena::{ArenaBounds, ConfineToArena},
+    collision::{Collider, CollisionStarted},
+    movement::Velocity,
+    settings_menu::OpenSettings,
+};
+use rand::prelude::*;
+
+use crate::actions::{Action, MoveAxis};
+use crate::components::*;
+use crate::resources::*;
+use crate::{PLAYER_SIZE, PLAYER_SPEED, PLAYER_WIDTH, STAR_ROTATE_SPEED, STAR_SIZE, STAR_SPEED};
+
+pub fn load_game_assets(mut commands: Commands, asset_server: Option<Res<AssetServer>>) {
+    let Some(asset_server) = asset_server else {
+        commands.init_resource::<GameAssets>();
+        return;
+    };
+    commands.insert_resource(GameAssets {
+        player_texture: asset_server.load("sprites/player_sprite.png"),
+        star_texture: asset_server.load("sprites/star.png"),
+    });
+}
+
+//spawning entities
+pub fn spawn_player_one(
+    mut commands: Commands,
+    arena: Res<ArenaBounds>,
+    game_assets: Res<GameAssets>
+) {
+    commands.spawn (
+        (
+            SpriteBundle {
+                transform: Transform::from_xyz(15.0, arena.height / 2.0, 0.0),
+                texture: game_assets.player_texture.clone(),
+                ..default()
+            },
+            PlayerOne{},
+            Velocity::default(),
+            ConfineToArena::new(Vec2::new(PLAYER_WIDTH, PLAYER_SIZE)),
+            Collider::aabb(Vec2::new(PLAYER_WIDTH / 2.0, PLAYER_SIZE / 2.0), PADDLE_COLLISION),
+        )
+    );
+}
+
+pub fn spawn_player_two(
+    mut commands: Commands,
+    arena: Res<ArenaBounds>,
+    game_assets: Res<GameAssets>
+) {
+    commands.spawn (
+        (
+            SpriteBundle {
+                transform: Transform::from_xyz( arena.width - 15.0, arena.height / 2.0, 0.0),
+                texture: game_assets.player_texture.clone(),
+                ..default()
+            },
+            PlayerTwo{},
+            Collider::aabb(Vec2::new(PLAYER_WIDTH / 2.0, PLAYER_SIZE / 2.0), PADDLE_COLLISION),
+        )
+    );
+}
+pub fn spawn_star(
+    mut commands: Commands,
+    arena: Res<ArenaBounds>,
+    game_assets: Res<GameAssets>,
+    mut rng: ResMut<SimulationRng>,
+) {
+    let rand_x = rng.gen_range(0..2);
+    let rand_y = rng.gen_range(0..2);
+    let x_value = if rand_x == 0 { 1.0 } else { -1.0 };
+    let y_value: f32 = if rand_y == 0 { 0.4 } else { -0.4 };
+
+
+    commands.spawn(
+        (
+            SpriteBundle{
+                transform: Transform::from_xyz(arena.width / 2.0, arena.height / 2.0, 0.0),
+                texture: game_assets.star_texture.clone(),
+                ..default()
+            },
+            Star{},
+            Velocity(Vec2::new(x_value, y_value).normalize() * STAR_SPEED),
+            Collider::aabb(Vec2::splat(STAR_SIZE / 2.0), STAR_COLLISION),
+        )
+    );
+}
+
+
+
+
+//movement and confinement systems
+
+pub fn player_one_movement(
+    move_axis: Res<MoveAxis>,
+    mut player_query: Query<&mut Velocity, With<PlayerOne>>,
+) {
+    if let Ok(mut velocity) = player_query.get_single_mut() {
+        velocity.0 = Vec2::new(0.0, move_axis.value * PLAYER_SPEED);
+    }
+}
+
+pub fn spin_star(
+    mut star_query: Query<&mut Transform, With<Star>>,
+    fixed_time: Res<FixedTime>
+) {
+    for mut transform in star_query.iter_mut() {
+        transform.rotation *= Quat::from_rotation_z( STAR_ROTATE_SPEED * fixed_time.period.as_secs_f32());
+    }
+}
+
+pub fn change_star_direction(
+    mut star_query: Query<(&Transform, &mut Velocity), With<Star>>,
+    arena: Res<ArenaBounds>,
+) {
+    let max_y = arena.height - (STAR_SIZE / 2.0);
+    let min_y: f32 = 0.0 + (STAR_SIZE / 2.0);
+
+    for(transform, mut velocity) in star_query.iter_mut() {
+        let translation: Vec3 = transform.translation;
+        if translation.y < min_y { 
+            velocity.0.y *= -1.0;
+        }
+        if translation.y > max_y {
+            velocity.0.y *= -1.0;
+        }
+
+    }
+}
+
+
+pub fn player_two_movement(
+    star_query: Query<&Transform, With<Star>>,
+    mut player_two_query: Query<&mut Transform, (With<PlayerTwo>, Without<Star>)>,
+) {
+    if let Ok(mut player_transform) = player_two_query.get_single_mut() {
+        if let Ok(star_transform) = star_query.get_single() {
+            player_transform.translation.y = star_transform.translation.y;
+        }
+    }
+}
+
+
+// the collision only says the star touched a paddle, which way it goes next is decided here
+pub fn star_paddle_collision(
+    mut collisions: EventReader<CollisionStarted>,
+    mut star_query: Query<(&Transform, &mut Velocity), With<Star>>,
+    paddle_query: Query<&Transform, Or<(With<PlayerOne>, With<PlayerTwo>)>>,
+) {
+    for CollisionStarted(a, b) in collisions.iter() {
+        let (star_entity, paddle_entity) = if star_query.contains(*a) { (*a, *b) } else { (*b, *a) };
+        let (Ok((star_transform, mut velocity)), Ok(paddle_transform)) = (star_query.get_mut(star_entity), paddle_query.get(paddle_entity)) else {
+            continue;
+        };
+        // always back towards the other side, even when it clips the end of the paddle on its way past
+        let away = (star_transform.translation.x - paddle_transform.translation.x).signum();
+        velocity.0.x = velocity.0.x.abs() * away;
+    }
+}
+
+pub fn open_settings(
+    actions: Res<Input<Action>>,
+    mut open_settings: EventWriter<OpenSettings>,
+) {
+    if actions.just_pressed(Action::Settings) {
+        open_settings.send(OpenSettings);
+    }
+}
+
+pub fn exit_game(
+    actions: Res<Input<Action>>,
+    mut app_exit_event_write: EventWriter<AppExit>,
+) {
+    if actions.just_pressed(Action::Quit) {
+        app_exit_event_write.send(AppExit);
+    }
+}
@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+use game_common::arena::ArenaBounds;
+use pong_game_project::{
+    components::{PlayerOne, PlayerTwo, Star},
+    headless_app,
+    resources::SimulationRng,
+    FIXED_TIMESTEP, PLAYER_SPEED, STAR_SIZE, STAR_SPEED,
+};
+
+fn position<T: Component>(app: &mut App) -> Vec3 {
+    app.world.query_filtered::<&Transform, With<T>>().single(&app.world).translation
+}
+
+#[test]
+fn a_rally_plays_out_without_a_window() {
+    let arena = ArenaBounds::default();
+    let mut app = headless_app(arena);
+    app.update();
+    assert_eq!(position::<Star>(&mut app), Vec3::new(arena.width / 2.0, arena.height / 2.0, 0.0));
+
+    // far enough for the star to reach the top or bottom wall and come back
+    let frame_distance = STAR_SPEED * FIXED_TIMESTEP;
+    for _ in 0..600 {
+        app.update();
+        let star = position::<Star>(&mut app);
+        assert!(star.y > -STAR_SIZE && star.y < arena.height + STAR_SIZE, "the star left through a wall at {:?}", star);
+        // the right paddle follows the star, at most a frame behind
+        assert!((position::<PlayerTwo>(&mut app).y - star.y).abs() <= frame_distance);
+    }
+}
+
+#[test]
+fn keyboard_moves_player_one() {
+    let mut app = headless_app(ArenaBounds::default());
+    app.update();
+    let start = position::<PlayerOne>(&mut app);
+
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::W);
+    for _ in 0..10 {
+        app.update();
+    }
+    let moved = position::<PlayerOne>(&mut app).y - start.y;
+    assert!(moved > 0.0 && moved <= 10.0 * PLAYER_SPEED * FIXED_TIMESTEP, "moved {}", moved);
+}
+
+#[test]
+fn same_seed_serves_the_same_rally() {
+    let mut first = headless_app(ArenaBounds::default());
+    let mut second = headless_app(ArenaBounds::default());
+    first.insert_resource(SimulationRng::new(42));
+    second.insert_resource(SimulationRng::new(42));
+    for _ in 0..600 {
+        first.update();
+        second.update();
+    }
+    assert_eq!(position::<Star>(&mut first), position::<Star>(&mut second));
+}